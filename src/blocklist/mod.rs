@@ -0,0 +1,509 @@
+//! Users and servers blocked from using the bot.
+//!
+//! Owners block an abusive user or a whole server with `block user` and `block server`, with a
+//! reason and, optionally, how long the block lasts. Blocked users and servers are ignored by the
+//! dispatcher without an answer, blocked users don't count as players in the polls, and blocked
+//! servers don't get polls anymore. Blocks are stored in `data/blocklist.json`.
+
+use chrono::{DateTime, Duration, Utc};
+
+use serenity::builder::CreateEmbed;
+use serenity::framework::standard::{Args, CommandError};
+use serenity::framework::StandardFramework;
+use serenity::model::misc::Mentionable;
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use serde_json::{from_reader, to_writer_pretty};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::RwLock;
+
+use dispatch;
+use dorothy::Module;
+use error::Error;
+use i18n::{tr, tr_with};
+use paginator::send_paginated;
+use permissions;
+use utils::{paginate_embed, Section};
+
+/// Name of the module.
+const NAME: &str = "blocklist";
+
+/// Why someone is blocked, and until when.
+#[derive(Clone, Serialize, Deserialize)]
+struct Block {
+    reason: Option<String>,
+    /// `None` if the block lasts until it's lifted.
+    until: Option<DateTime<Utc>>,
+    by: UserId,
+}
+
+impl Block {
+    fn is_active(&self) -> bool {
+        self.until.map(|until| Utc::now() < until).unwrap_or(true)
+    }
+
+    fn describe(&self, guild_id: Option<GuildId>) -> String {
+        let until = match self.until {
+            Some(until) => tr_with(
+                guild_id,
+                "blocklist.until",
+                &[("time", until.format("%Y-%m-%d %H:%M UTC").to_string())],
+            ),
+            None => tr(guild_id, "blocklist.forever"),
+        };
+        match self.reason {
+            Some(ref reason) => format!("{}, {}", until, reason),
+            None => until,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Blocks {
+    users: HashMap<UserId, Block>,
+    guilds: HashMap<GuildId, Block>,
+}
+
+impl Blocks {
+    /// Forgets the blocks that are over.
+    fn prune(&mut self) {
+        self.users.retain(|_, b| b.is_active());
+        self.guilds.retain(|_, b| b.is_active());
+    }
+}
+
+lazy_static! {
+    static ref BLOCKLIST: RwLock<Blocks> = RwLock::new(initialize_blocklist());
+}
+
+fn initialize_blocklist() -> Blocks {
+    let file = File::open("data/blocklist.json");
+    if file.is_err() {
+        return Blocks::default();
+    }
+    let file = file.unwrap();
+    from_reader(file).unwrap_or_else(|e| {
+        warn!("couldn't deserialize the blocklist: {:?}", e);
+        Blocks::default()
+    })
+}
+
+fn save_blocklist() -> Result<(), Error> {
+    let file = File::create("data/blocklist.json")?;
+
+    let blocklist = BLOCKLIST
+        .read()
+        .expect("couldn't lock BLOCKLIST for reading");
+    to_writer_pretty(file, &*blocklist)?;
+    Ok(())
+}
+
+/// Whether a user is blocked.
+pub fn is_user_blocked(user_id: UserId) -> bool {
+    let blocklist = BLOCKLIST
+        .read()
+        .expect("couldn't lock BLOCKLIST for reading");
+    blocklist
+        .users
+        .get(&user_id)
+        .map(Block::is_active)
+        .unwrap_or(false)
+}
+
+/// Whether a server is blocked.
+pub fn is_guild_blocked(guild_id: GuildId) -> bool {
+    let blocklist = BLOCKLIST
+        .read()
+        .expect("couldn't lock BLOCKLIST for reading");
+    blocklist
+        .guilds
+        .get(&guild_id)
+        .map(Block::is_active)
+        .unwrap_or(false)
+}
+
+/// Whether the author of a message can use the bot there. Owners are never blocked, so that they
+/// can lift blocks from anywhere.
+pub fn check(msg: &Message) -> bool {
+    if permissions::is_owner(msg.author.id) {
+        return true;
+    }
+    !is_user_blocked(msg.author.id) && !msg.guild_id.map(is_guild_blocked).unwrap_or(false)
+}
+
+/// Parses how long a block lasts, like `30m`, `12h`, `7d` or `2w`. Durations too long to be
+/// represented aren't durations.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let (i, unit) = text.char_indices().last()?;
+    let count = text[..i].parse::<i64>().ok().filter(|&c| c > 0)?;
+    let unit = match unit {
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    count
+        .checked_mul(unit)
+        .filter(|&seconds| seconds <= Duration::max_value().num_seconds())
+        .map(Duration::seconds)
+}
+
+/// Whether a word is written like a duration (a number and a unit), usable or not.
+fn looks_like_duration(text: &str) -> bool {
+    match text.char_indices().last() {
+        Some((i, unit)) => {
+            i > 0 && "mhdw".contains(unit) && text[..i].chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// Reads the optional duration and the reason at the end of the arguments of a block made by
+/// `by`. A duration that can't be used (zero, or ending too far in the future) is refused rather
+/// than taken for the reason of a block that would never end.
+fn read_block(guild_id: Option<GuildId>, by: UserId, args: &mut Args) -> Result<Block, Error> {
+    let first = args
+        .single_n::<String>()
+        .ok()
+        .filter(|first| looks_like_duration(first));
+    let until = match first {
+        Some(first) => {
+            let until = parse_duration(&first)
+                .and_then(|duration| Utc::now().checked_add_signed(duration))
+                .ok_or_else(|| {
+                    Error::InvalidArgument(tr_with(
+                        guild_id,
+                        "blocklist.bad_duration",
+                        &[("duration", first)],
+                    ))
+                })?;
+            args.skip();
+            Some(until)
+        }
+        None => None,
+    };
+    let reason = Some(args.rest().to_string()).filter(|r| !r.is_empty());
+
+    Ok(Block { reason, until, by })
+}
+
+fn block_user(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    let mut args = args;
+    let user_id = args
+        .single::<UserId>()
+        .map_err(|_| Error::InvalidArgument(tr(msg.guild_id, "blocklist.bad_user")))?;
+    if permissions::is_owner(user_id) {
+        return Err(Error::InvalidArgument(tr(msg.guild_id, "blocklist.owner")).into());
+    }
+    let block = read_block(msg.guild_id, msg.author.id, &mut args)?;
+    let described = block.describe(msg.guild_id);
+
+    {
+        let mut blocklist = BLOCKLIST
+            .write()
+            .expect("couldn't lock BLOCKLIST for writing");
+        blocklist.prune();
+        blocklist.users.insert(user_id, block);
+    }
+    save_blocklist()?;
+    info!(
+        "User {} blocked by {}: {}",
+        user_id, msg.author.id, described
+    );
+
+    // In an embed, so that the blocked user isn't pinged.
+    msg.channel_id.send_message(|m| {
+        m.embed(|e| {
+            e.description(tr_with(
+                msg.guild_id,
+                "blocklist.user_blocked",
+                &[("user", user_id.mention()), ("block", described)],
+            ))
+        })
+    })?;
+
+    Ok(())
+}
+
+fn block_guild(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    let mut args = args;
+    let guild_id = args
+        .single::<u64>()
+        .map(GuildId)
+        .map_err(|_| Error::InvalidArgument(tr(msg.guild_id, "blocklist.bad_guild")))?;
+    let block = read_block(msg.guild_id, msg.author.id, &mut args)?;
+    let described = block.describe(msg.guild_id);
+
+    {
+        let mut blocklist = BLOCKLIST
+            .write()
+            .expect("couldn't lock BLOCKLIST for writing");
+        blocklist.prune();
+        blocklist.guilds.insert(guild_id, block);
+    }
+    save_blocklist()?;
+    info!(
+        "Server {} blocked by {}: {}",
+        guild_id, msg.author.id, described
+    );
+
+    msg.channel_id.send_message(|m| {
+        m.content(tr_with(
+            msg.guild_id,
+            "blocklist.guild_blocked",
+            &[("guild", guild_name(guild_id)), ("block", described)],
+        ))
+    })?;
+
+    Ok(())
+}
+
+fn unblock_user(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    let mut args = args;
+    let user_id = args
+        .single::<UserId>()
+        .map_err(|_| Error::InvalidArgument(tr(msg.guild_id, "blocklist.bad_user")))?;
+
+    let removed = {
+        let mut blocklist = BLOCKLIST
+            .write()
+            .expect("couldn't lock BLOCKLIST for writing");
+        blocklist.prune();
+        blocklist.users.remove(&user_id).is_some()
+    };
+    save_blocklist()?;
+    if removed {
+        info!("User {} unblocked by {}", user_id, msg.author.id);
+    }
+
+    let key = if removed {
+        "blocklist.user_unblocked"
+    } else {
+        "blocklist.user_not_blocked"
+    };
+    msg.channel_id.send_message(|m| {
+        m.embed(|e| e.description(tr_with(msg.guild_id, key, &[("user", user_id.mention())])))
+    })?;
+
+    Ok(())
+}
+
+fn unblock_guild(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    let mut args = args;
+    let guild_id = args
+        .single::<u64>()
+        .map(GuildId)
+        .map_err(|_| Error::InvalidArgument(tr(msg.guild_id, "blocklist.bad_guild")))?;
+
+    let removed = {
+        let mut blocklist = BLOCKLIST
+            .write()
+            .expect("couldn't lock BLOCKLIST for writing");
+        blocklist.prune();
+        blocklist.guilds.remove(&guild_id).is_some()
+    };
+    save_blocklist()?;
+    if removed {
+        info!("Server {} unblocked by {}", guild_id, msg.author.id);
+    }
+
+    let key = if removed {
+        "blocklist.guild_unblocked"
+    } else {
+        "blocklist.guild_not_blocked"
+    };
+    msg.channel_id.send_message(|m| {
+        m.content(tr_with(
+            msg.guild_id,
+            key,
+            &[("guild", guild_name(guild_id))],
+        ))
+    })?;
+
+    Ok(())
+}
+
+/// Name of a server followed by its ID, or only its ID if the bot isn't in it.
+fn guild_name(guild_id: GuildId) -> String {
+    match guild_id.to_guild_cached() {
+        Some(guild) => format!("{} ({})", guild.read().name, guild_id),
+        None => guild_id.to_string(),
+    }
+}
+
+fn list(_: &mut Context, msg: &Message, _: Args) -> Result<(), CommandError> {
+    let (mut users, mut guilds) = {
+        let blocklist = BLOCKLIST
+            .read()
+            .expect("couldn't lock BLOCKLIST for reading");
+        let users = blocklist
+            .users
+            .iter()
+            .filter(|(_, b)| b.is_active())
+            .map(|(id, b)| format!("{}: {}", id.mention(), b.describe(msg.guild_id)))
+            .collect::<Vec<String>>();
+        let guilds = blocklist
+            .guilds
+            .iter()
+            .filter(|(_, b)| b.is_active())
+            .map(|(id, b)| format!("{}: {}", guild_name(*id), b.describe(msg.guild_id)))
+            .collect::<Vec<String>>();
+        (users, guilds)
+    };
+    if users.is_empty() && guilds.is_empty() {
+        msg.channel_id
+            .send_message(|m| m.content(tr(msg.guild_id, "blocklist.empty")))?;
+        return Ok(());
+    }
+    users.sort();
+    guilds.sort();
+
+    let sections = vec![("blocklist.users", users), ("blocklist.guilds", guilds)]
+        .into_iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .map(|(key, lines)| Section::new(tr(msg.guild_id, key), lines))
+        .collect();
+    let embeds = paginate_embed(
+        &CreateEmbed::default(),
+        sections,
+        &tr(msg.guild_id, "cont"),
+    );
+    send_paginated(msg, embeds)?;
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct Blocklist;
+
+impl Module for Blocklist {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Users and servers blocked from using the bot"
+    }
+
+    fn can_be_disabled(&self) -> bool {
+        false
+    }
+
+    fn register(&self, framework: StandardFramework) -> StandardFramework {
+        framework.group("Blocklist", |g| {
+            dispatch::index(
+                NAME,
+                Permissions::empty(),
+                g.desc("Users and servers blocked from using the bot")
+                    .owners_only(true)
+                    .command("block user", |c| {
+                        c.desc("Blocks a user from using the bot, for a while (like `12h`, `7d` or `2w`) or until they're unblocked.")
+                            .usage("<@user|ID> [duration] [reason]")
+                            .min_args(1)
+                            .exec(block_user)
+                    }).command("block server", |c| {
+                        c.desc("Blocks a server from using the bot, for a while (like `12h`, `7d` or `2w`) or until it's unblocked.")
+                            .usage("<server ID> [duration] [reason]")
+                            .min_args(1)
+                            .exec(block_guild)
+                    }).command("unblock user", |c| {
+                        c.desc("Lets a blocked user use the bot again.")
+                            .usage("<@user|ID>")
+                            .num_args(1)
+                            .exec(unblock_user)
+                    }).command("unblock server", |c| {
+                        c.desc("Lets a blocked server use the bot again.")
+                            .usage("<server ID>")
+                            .num_args(1)
+                            .exec(unblock_guild)
+                    }).command("blocklist", |c| {
+                        c.desc("Lists the blocked users and servers.")
+                            .max_args(0)
+                            .exec(list)
+                    }),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_have_a_unit() {
+        assert_eq!(parse_duration("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_duration("12h"), Some(Duration::hours(12)));
+        assert_eq!(parse_duration("7d"), Some(Duration::days(7)));
+        assert_eq!(parse_duration("2w"), Some(Duration::weeks(2)));
+        assert_eq!(parse_duration("1w"), Some(Duration::days(7)));
+    }
+
+    #[test]
+    fn other_words_are_not_durations() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("0d"), None);
+        assert_eq!(parse_duration("-3d"), None);
+        assert_eq!(parse_duration("3"), None);
+        assert_eq!(parse_duration("3y"), None);
+        assert_eq!(parse_duration("spam"), None);
+        // Multi-byte last characters.
+        assert_eq!(parse_duration("café"), None);
+        assert_eq!(parse_duration("3é"), None);
+        assert_eq!(parse_duration("é"), None);
+        assert_eq!(parse_duration("３d"), None);
+    }
+
+    #[test]
+    fn huge_durations_are_not_durations() {
+        assert_eq!(parse_duration("99999999999999w"), None);
+        assert_eq!(parse_duration("9223372036854775807m"), None);
+        assert_eq!(parse_duration("99999999999999999999d"), None);
+        // Representable, but not after now.
+        let duration = parse_duration("9999999999w");
+        assert!(duration.is_some());
+        assert_eq!(Utc::now().checked_add_signed(duration.unwrap()), None);
+    }
+
+    fn read(text: &str) -> Result<Block, Error> {
+        read_block(None, UserId(1), &mut Args::new(text, &[" ".to_string()]))
+    }
+
+    #[test]
+    fn blocks_have_a_duration_and_a_reason() {
+        let block = read("2w spam").unwrap();
+        let until = block.until.unwrap();
+        assert!(until > Utc::now() + Duration::days(13) && until <= Utc::now() + Duration::weeks(2));
+        assert_eq!(block.reason, Some("spam".to_string()));
+        assert_eq!(block.by, UserId(1));
+
+        let block = read("spam in every channel").unwrap();
+        assert_eq!(block.until, None);
+        assert_eq!(block.reason, Some("spam in every channel".to_string()));
+
+        let block = read("").unwrap();
+        assert_eq!(block.until, None);
+        assert_eq!(block.reason, None);
+    }
+
+    #[test]
+    fn unusable_durations_are_refused() {
+        for text in &["9999999999w spam", "99999999999999999999d spam", "0d spam"] {
+            match read(text) {
+                Err(Error::InvalidArgument(_)) => {}
+                Err(e) => panic!("wrong error for {}: {:?}", text, e),
+                Ok(block) => panic!("{} accepted, until {:?}", text, block.until),
+            }
+        }
+        // Not written like a duration: part of the reason.
+        let block = read("3y spam").unwrap();
+        assert_eq!(block.until, None);
+        assert_eq!(block.reason, Some("3y spam".to_string()));
+    }
+}
@@ -0,0 +1,93 @@
+//! Actions confirmed with a reaction.
+//!
+//! `ask` posts a message with a ✅ reaction; the action runs once the user who invoked the command
+//! clicks it. If they don't within `confirmation.timeout` seconds (60 by default), the action is
+//! forgotten.
+//!
+//! The reactions are received by `Dorothy`, which forwards them to `handle_reaction`.
+
+use serenity::builder::CreateEmbed;
+use serenity::model::prelude::*;
+use serenity::Result as SerenityResult;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use SETTINGS;
+
+const CONFIRM: &str = "✅";
+
+/// Timeout used if `confirmation.timeout` isn't set.
+const DEFAULT_TIMEOUT: u64 = 60;
+
+/// Something to do once confirmed.
+pub type Action = Box<Fn() + Send + Sync>;
+
+lazy_static! {
+    static ref PENDING: RwLock<HashMap<MessageId, Pending>> = {
+        RwLock::new(HashMap::new())
+    };
+}
+
+/// An action waiting for its confirmation.
+struct Pending {
+    author: UserId,
+    action: Action,
+    expires: Instant,
+}
+
+fn timeout() -> Duration {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    Duration::from_secs(
+        settings
+            .get::<u64>("confirmation.timeout")
+            .unwrap_or(DEFAULT_TIMEOUT),
+    )
+}
+
+/// Asks the author of a command to confirm an action, with a message made of a text and an
+/// embed.
+pub fn ask(msg: &Message, content: String, embed: CreateEmbed, action: Action) -> SerenityResult<()> {
+    let sent = msg.channel_id.send_message(|m| {
+        m.content(content)
+            .embed(|_| embed)
+            .reactions(vec![ReactionType::from(CONFIRM)])
+    })?;
+
+    let mut pending = PENDING.write().expect("couldn't lock PENDING for writing");
+    let now = Instant::now();
+    pending.retain(|_, p| p.expires > now);
+    pending.insert(
+        sent.id,
+        Pending {
+            author: msg.author.id,
+            action,
+            expires: now + timeout(),
+        },
+    );
+
+    Ok(())
+}
+
+/// Runs the action of a message if the reaction is its author confirming it in time.
+pub fn handle_reaction(reaction: &Reaction) {
+    match reaction.emoji {
+        ReactionType::Unicode(ref name) if name.trim_end_matches('\u{fe0f}') == CONFIRM => (),
+        _ => return,
+    }
+
+    let pending = {
+        let mut pending = PENDING.write().expect("couldn't lock PENDING for writing");
+        match pending.get(&reaction.message_id) {
+            Some(p) if p.author == reaction.user_id => (),
+            _ => return,
+        }
+        // The action is only run once.
+        pending.remove(&reaction.message_id).unwrap()
+    };
+
+    if pending.expires > Instant::now() {
+        (pending.action)();
+    }
+}
@@ -0,0 +1,95 @@
+//! Conversations with a user, one message at a time.
+//!
+//! `start` makes the next messages of a user in a channel go to a `Conversation` instead of being
+//! ignored, until it says it's over. Messages starting with the command prefix are left to the
+//! framework. A conversation without any answer for `conversation.timeout` seconds (5 minutes by
+//! default) is dropped.
+//!
+//! The messages are received by `Dorothy`, which forwards them to `handle_message`.
+
+use serenity::model::prelude::*;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use SETTINGS;
+
+/// Timeout used if `conversation.timeout` isn't set.
+const DEFAULT_TIMEOUT: u64 = 300;
+
+/// Something answering a user's messages.
+pub trait Conversation: Send + Sync {
+    /// Handles a message of the user. Returns whether the conversation goes on.
+    fn answer(&mut self, msg: &Message) -> bool;
+}
+
+lazy_static! {
+    static ref CONVERSATIONS: RwLock<HashMap<(ChannelId, UserId), Ongoing>> = {
+        RwLock::new(HashMap::new())
+    };
+}
+
+struct Ongoing {
+    conversation: Box<Conversation>,
+    expires: Instant,
+}
+
+fn timeout() -> Duration {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    Duration::from_secs(
+        settings
+            .get::<u64>("conversation.timeout")
+            .unwrap_or(DEFAULT_TIMEOUT),
+    )
+}
+
+/// Starts a conversation with a user in a channel, replacing the one they had there.
+pub fn start(channel_id: ChannelId, user_id: UserId, conversation: Box<Conversation>) {
+    let mut conversations = CONVERSATIONS
+        .write()
+        .expect("couldn't lock CONVERSATIONS for writing");
+    let now = Instant::now();
+    conversations.retain(|_, c| c.expires > now);
+    conversations.insert(
+        (channel_id, user_id),
+        Ongoing {
+            conversation,
+            expires: now + timeout(),
+        },
+    );
+}
+
+/// Gives a message to the conversation of its author in its channel, if there's one.
+pub fn handle_message(msg: &Message) {
+    let prefix = {
+        let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+        settings.get_str("prefix").unwrap_or_default()
+    };
+    if msg.author.bot || (!prefix.is_empty() && msg.content.starts_with(&prefix)) {
+        return;
+    }
+
+    let key = (msg.channel_id, msg.author.id);
+    // The conversation is taken out of the map while it answers, so that it doesn't keep the lock
+    // while talking to Discord.
+    let ongoing = CONVERSATIONS
+        .write()
+        .expect("couldn't lock CONVERSATIONS for writing")
+        .remove(&key);
+    let mut ongoing = match ongoing {
+        Some(ref o) if o.expires <= Instant::now() => return,
+        Some(o) => o,
+        None => return,
+    };
+
+    if ongoing.conversation.answer(msg) {
+        ongoing.expires = Instant::now() + timeout();
+        CONVERSATIONS
+            .write()
+            .expect("couldn't lock CONVERSATIONS for writing")
+            .entry(key)
+            // A new conversation may have been started meanwhile, it wins.
+            .or_insert(ongoing);
+    }
+}
@@ -0,0 +1,302 @@
+//! Cooldowns of the commands, so that they can't be spammed.
+//!
+//! After using a command, a user waits `cooldowns.user` seconds before using it again, and a
+//! server waits `cooldowns.guild` seconds before anyone uses it again there. Commands can have
+//! their own cooldowns in the `cooldowns.commands` table of the settings, and servers can override
+//! them with `cooldown`. A user using a command too early is told how long to wait once, then
+//! ignored until the end of the cooldown.
+//!
+//! Cooldowns start when the command is dispatched, before it runs: a command that fails, on bad
+//! arguments for instance, still starts them. Otherwise failing commands, which answer with an
+//! error, could be spammed.
+
+use serenity::framework::standard::{Args, CommandError};
+use serenity::framework::StandardFramework;
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use serde_json::{from_reader, to_writer_pretty};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use config::Value;
+
+use dispatch;
+use dorothy::Module;
+use error::Error;
+use i18n::{tr, tr_with};
+use permissions;
+use SETTINGS;
+
+/// Name of the module.
+const NAME: &str = "cooldowns";
+
+/// Seconds to wait before using a command again, by the same user and in the same server.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Cooldown {
+    user: u64,
+    guild: u64,
+}
+
+/// Who waits for the end of a cooldown.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Scope {
+    User(UserId),
+    Guild(GuildId),
+}
+
+/// A cooldown going on.
+struct Waiting {
+    until: Instant,
+    told: bool,
+}
+
+lazy_static! {
+    /// Cooldowns set by the servers, by command.
+    static ref OVERRIDES: RwLock<HashMap<GuildId, HashMap<String, Cooldown>>> = {
+        RwLock::new(initialize_overrides())
+    };
+    static ref WAITING: Mutex<HashMap<(String, Scope), Waiting>> = Mutex::new(HashMap::new());
+}
+
+fn initialize_overrides() -> HashMap<GuildId, HashMap<String, Cooldown>> {
+    let file = File::open("data/cooldowns.json");
+    if file.is_err() {
+        return HashMap::new();
+    }
+    let file = file.unwrap();
+    from_reader(file).unwrap_or_else(|e| {
+        warn!("couldn't deserialize cooldowns: {:?}", e);
+        HashMap::new()
+    })
+}
+
+fn save_overrides() -> Result<(), Error> {
+    let file = File::create("data/cooldowns.json")?;
+
+    let overrides = OVERRIDES
+        .read()
+        .expect("couldn't lock OVERRIDES for reading");
+    to_writer_pretty(file, &*overrides)?;
+    Ok(())
+}
+
+/// Cooldown of a command from the settings.
+fn configured(command: &str) -> Cooldown {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    let default = Cooldown {
+        user: settings.get::<u64>("cooldowns.user").unwrap_or(0),
+        guild: settings.get::<u64>("cooldowns.guild").unwrap_or(0),
+    };
+    // Looked up by hand, since command names can have spaces.
+    let table = settings
+        .get_table("cooldowns.commands")
+        .ok()
+        .and_then(|commands| commands.get(command).cloned())
+        .and_then(|command| command.into_table().ok());
+    let table = match table {
+        Some(table) => table,
+        None => return default,
+    };
+    let get = |key: &str| {
+        table
+            .get(key)
+            .cloned()
+            .and_then(|v: Value| v.into_int().ok())
+            .map(|v| v.max(0) as u64)
+    };
+
+    Cooldown {
+        user: get("user").unwrap_or(default.user),
+        guild: get("guild").unwrap_or(default.guild),
+    }
+}
+
+/// Cooldown of a command in a server.
+fn cooldown(guild_id: Option<GuildId>, command: &str) -> Cooldown {
+    let overridden = guild_id.and_then(|guild_id| {
+        let overrides = OVERRIDES
+            .read()
+            .expect("couldn't lock OVERRIDES for reading");
+        overrides
+            .get(&guild_id)
+            .and_then(|commands| commands.get(command).cloned())
+    });
+    overridden.unwrap_or_else(|| configured(command))
+}
+
+/// Seconds left, rounded up.
+fn seconds(duration: Duration) -> i64 {
+    let rounded = if duration.subsec_nanos() > 0 { 1 } else { 0 };
+    (duration.as_secs() + rounded) as i64
+}
+
+/// Checks that the author of a message can use a command now, and starts its cooldowns if they
+/// can, whether the command then succeeds or not. If they can't, returns the seconds left if they
+/// must be told.
+pub fn check(msg: &Message, command: &str) -> Result<(), Option<i64>> {
+    if permissions::is_owner(msg.author.id) {
+        return Ok(());
+    }
+    let cooldown = cooldown(msg.guild_id, command);
+    let mut scopes = vec![(Scope::User(msg.author.id), cooldown.user)];
+    if let Some(guild_id) = msg.guild_id {
+        scopes.push((Scope::Guild(guild_id), cooldown.guild));
+    }
+
+    let now = Instant::now();
+    let mut waiting = WAITING.lock().expect("couldn't lock WAITING");
+    waiting.retain(|_, w| w.until > now);
+
+    let mut left = None;
+    let mut tell = false;
+    for &(scope, _) in &scopes {
+        if let Some(w) = waiting.get_mut(&(command.to_string(), scope)) {
+            left = left.max(Some(w.until - now));
+            tell |= !w.told;
+            w.told = true;
+        }
+    }
+    if let Some(left) = left {
+        return Err(if tell { Some(seconds(left)) } else { None });
+    }
+
+    for (scope, secs) in scopes {
+        if secs > 0 {
+            waiting.insert(
+                (command.to_string(), scope),
+                Waiting {
+                    until: now + Duration::from_secs(secs),
+                    told: false,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+fn set_cooldown(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    let mut args = args;
+    // Unwrap is safe here because this command is only available in servers.
+    let server_id = msg.guild_id.unwrap();
+
+    let command = args.single_quoted::<String>()?;
+    if dispatch::module_of(&command).is_none() {
+        return Err(Error::InvalidArgument(tr_with(
+            Some(server_id),
+            "cooldowns.unknown",
+            &[("command", command)],
+        )).into());
+    }
+
+    if args.is_empty() {
+        let current = cooldown(Some(server_id), &command);
+        msg.channel_id.send_message(|m| {
+            m.content(tr_with(
+                Some(server_id),
+                "cooldowns.current",
+                &[
+                    ("command", command.clone()),
+                    ("user", current.user.to_string()),
+                    ("guild", current.guild.to_string()),
+                ],
+            ))
+        })?;
+        return Ok(());
+    }
+
+    let bad_seconds = || Error::InvalidArgument(tr(Some(server_id), "cooldowns.bad_seconds"));
+    let first = args.single::<String>()?;
+    let new = if first == "default" {
+        None
+    } else {
+        let user = first.parse::<u64>().map_err(|_| bad_seconds())?;
+        let guild = match args.single::<String>() {
+            Ok(guild) => guild.parse::<u64>().map_err(|_| bad_seconds())?,
+            // Keeps the server's cooldown, overridden or not.
+            Err(_) => cooldown(Some(server_id), &command).guild,
+        };
+        Some(Cooldown { user, guild })
+    };
+
+    {
+        let mut overrides = OVERRIDES
+            .write()
+            .expect("couldn't lock OVERRIDES for writing");
+        match new {
+            Some(new) => {
+                overrides
+                    .entry(server_id)
+                    .or_default()
+                    .insert(command.clone(), new);
+            }
+            None => {
+                if let Some(commands) = overrides.get_mut(&server_id) {
+                    commands.remove(&command);
+                }
+                let empty = overrides
+                    .get(&server_id)
+                    .map(HashMap::is_empty)
+                    .unwrap_or(false);
+                if empty {
+                    overrides.remove(&server_id);
+                }
+            }
+        }
+    }
+    save_overrides()?;
+
+    let current = cooldown(Some(server_id), &command);
+    msg.channel_id.send_message(|m| {
+        m.content(tr_with(
+            Some(server_id),
+            "cooldowns.set",
+            &[
+                ("command", command),
+                ("user", current.user.to_string()),
+                ("guild", current.guild.to_string()),
+            ],
+        ))
+    })?;
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct Cooldowns;
+
+impl Module for Cooldowns {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Cooldowns of the commands"
+    }
+
+    fn can_be_disabled(&self) -> bool {
+        false
+    }
+
+    fn register(&self, framework: StandardFramework) -> StandardFramework {
+        framework.group("Cooldowns", |g| {
+            dispatch::index(
+                NAME,
+                Permissions::MANAGE_GUILD,
+                g.desc("Cooldowns of the commands")
+                    .guild_only(true)
+                    .command("cooldown", |c| {
+                        c.desc("Shows or sets how many seconds a user, and the whole server, wait between two uses of a command. `default` goes back to the bot's settings.")
+                            .usage("<command> [<user seconds> [server seconds] | default]")
+                            .min_args(1)
+                            .max_args(3)
+                            .exec(set_cooldown)
+                    }),
+            )
+        })
+    }
+}
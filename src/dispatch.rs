@@ -0,0 +1,211 @@
+//! Answers to the commands that couldn't be run.
+//!
+//! Each reason gets its own answer: the usage of the command when the arguments are wrong, the
+//! missing permissions, the time left when rate limited... The answers are deleted after
+//! `dispatch.delete_after` seconds (15 by default) to keep the channels clean.
+//!
+//! The framework doesn't tell which command failed nor lets its usage be read, so modules pass
+//! their groups through `index` when registering them, which also keeps the permissions the
+//! commands need by default. Commands of modules disabled in a server, and commands of blocked
+//! users and servers, are ignored without an answer.
+
+use serenity::framework::standard::{CommandOrAlias, CreateGroup, DispatchError};
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::*;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::thread;
+use std::time::Duration;
+
+use blocklist;
+use i18n::{tr, tr_with};
+use modules;
+use utils::permission_names;
+use SETTINGS;
+
+/// Delay used if `dispatch.delete_after` isn't set.
+const DEFAULT_DELETE_AFTER: u64 = 15;
+
+/// What's known about a command.
+struct Indexed {
+    module: &'static str,
+    usage: String,
+    permissions: Permissions,
+}
+
+lazy_static! {
+    /// The commands, by name.
+    static ref COMMANDS: RwLock<HashMap<String, Indexed>> = RwLock::new(HashMap::new());
+}
+
+/// Remembers the module, the usage and the permissions needed by default of the commands of a
+/// group. `permissions` are needed on top of the ones the framework enforces. Used on the finished
+/// group, when registering it.
+pub fn index(module: &'static str, permissions: Permissions, group: CreateGroup) -> CreateGroup {
+    {
+        let mut commands = COMMANDS.write().expect("couldn't lock COMMANDS for writing");
+        for (name, command) in &group.0.commands {
+            if let CommandOrAlias::Command(ref command) = *command {
+                let options = command.options();
+                commands.insert(
+                    name.clone(),
+                    Indexed {
+                        module,
+                        usage: options.usage.clone().unwrap_or_default(),
+                        permissions: permissions
+                            | group.0.required_permissions
+                            | options.required_permissions,
+                    },
+                );
+            }
+        }
+    }
+    group
+}
+
+/// Module of a command.
+pub fn module_of(name: &str) -> Option<&'static str> {
+    let commands = COMMANDS.read().expect("couldn't lock COMMANDS for reading");
+    commands.get(name).map(|command| command.module)
+}
+
+/// Permissions needed by default to use a command.
+pub fn default_permissions(name: &str) -> Option<Permissions> {
+    let commands = COMMANDS.read().expect("couldn't lock COMMANDS for reading");
+    commands.get(name).map(|command| command.permissions)
+}
+
+/// Calls `f` with the name and what's known of the command of a message, if it's known.
+fn with_command<T, F: FnOnce(&str, &Indexed) -> T>(prefix: &str, content: &str, f: F) -> Option<T> {
+    let content = content.trim_start_matches(prefix);
+    let commands = COMMANDS.read().expect("couldn't lock COMMANDS for reading");
+    // Commands can have several words: the longest matching name is the command used.
+    commands
+        .iter()
+        .filter(|(name, _)| {
+            content == name.as_str() || content.starts_with(&format!("{} ", name))
+        }).max_by_key(|(name, _)| name.len())
+        .map(|(name, command)| f(name, command))
+}
+
+/// How to use the command of a message, like `!pmskip <dates> [reason]`.
+fn usage(prefix: &str, content: &str) -> Option<String> {
+    with_command(prefix, content, |name, command| {
+        format!("{}{} {}", prefix, name, command.usage)
+            .trim()
+            .to_string()
+    })
+}
+
+fn delete_after() -> Duration {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    Duration::from_secs(
+        settings
+            .get::<u64>("dispatch.delete_after")
+            .unwrap_or(DEFAULT_DELETE_AFTER),
+    )
+}
+
+/// The answer to a command that couldn't be run, if there's one.
+fn answer(msg: &Message, error: DispatchError) -> Option<String> {
+    let guild_id = msg.guild_id;
+    let prefix = {
+        let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+        settings.get_str("prefix").unwrap_or_default()
+    };
+    let with_usage = |content: String| match usage(&prefix, &msg.content) {
+        Some(usage) => format!(
+            "{}\n{}",
+            content,
+            tr_with(guild_id, "dispatch.usage", &[("usage", usage)])
+        ),
+        None => content,
+    };
+
+    let answer = match error {
+        DispatchError::NotEnoughArguments { min, given } => with_usage(tr_with(
+            guild_id,
+            "dispatch.not_enough_arguments",
+            &[("min", min.to_string()), ("given", given.to_string())],
+        )),
+        DispatchError::TooManyArguments { max, given } => with_usage(tr_with(
+            guild_id,
+            "dispatch.too_many_arguments",
+            &[("max", max.to_string()), ("given", given.to_string())],
+        )),
+        DispatchError::LackOfPermissions(required) => {
+            // Only name the permissions the author doesn't have in the channel.
+            let missing = msg
+                .guild()
+                .map(|guild| required - guild.read().permissions_in(msg.channel_id, msg.author.id))
+                .filter(|missing| !missing.is_empty())
+                .unwrap_or(required);
+            tr_with(
+                guild_id,
+                "dispatch.permissions",
+                &[("permissions", permission_names(missing))],
+            )
+        }
+        DispatchError::OnlyForOwners => tr(guild_id, "dispatch.owners_only"),
+        DispatchError::RateLimited(seconds) => tr_with(
+            guild_id,
+            "dispatch.rate_limited",
+            &[("seconds", seconds.to_string())],
+        ),
+        DispatchError::BlockedUser => tr(guild_id, "dispatch.blocked_user"),
+        DispatchError::BlockedGuild => tr(guild_id, "dispatch.blocked_guild"),
+        DispatchError::BlockedChannel => tr(guild_id, "dispatch.blocked_channel"),
+        DispatchError::CommandDisabled(command) => tr_with(
+            guild_id,
+            "dispatch.disabled",
+            &[("command", command)],
+        ),
+        DispatchError::OnlyForGuilds => tr(guild_id, "dispatch.guild_only"),
+        DispatchError::OnlyForDM => tr(guild_id, "dispatch.dm_only"),
+        DispatchError::LackingRole => tr(guild_id, "dispatch.lacking_role"),
+        DispatchError::CheckFailed => {
+            let module = with_command(&prefix, &msg.content, |_, command| command.module);
+            match module {
+                Some(module) if !modules::is_enabled(guild_id, module) => return None,
+                _ => tr(guild_id, "dispatch.check_failed"),
+            }
+        }
+        // Bots and webhooks aren't answered.
+        DispatchError::IgnoredBot | DispatchError::WebhookAuthor => return None,
+    };
+
+    Some(answer)
+}
+
+/// Tells the author of a command why it couldn't be run, and deletes the answer after a while.
+/// Blocked users and servers aren't answered.
+pub fn handle_error(msg: &Message, error: DispatchError) {
+    info!(
+        "Command of {} ({}) in channel {} not run: {:?}",
+        msg.author.name, msg.author.id, msg.channel_id, error
+    );
+    // The framework checks the command before the blocklist is looked at.
+    if !blocklist::check(msg) {
+        return;
+    }
+    let content = match answer(msg, error) {
+        Some(content) => content,
+        None => return,
+    };
+
+    let sent = match msg.channel_id.send_message(|m| m.content(content)) {
+        Ok(sent) => sent,
+        Err(e) => {
+            warn!("Couldn't answer a command that wasn't run: {:?}", e);
+            return;
+        }
+    };
+    let delay = delete_after();
+    thread::spawn(move || {
+        thread::sleep(delay);
+        if let Err(e) = sent.delete() {
+            warn!("Couldn't delete the answer to a command: {:?}", e);
+        }
+    });
+}
@@ -1,16 +1,175 @@
+//! The bot itself: the modules it's made of, and the gateway events given to them.
+//!
+//! A module registers its commands in the framework, and can react to the bot starting and
+//! stopping and to gateway events by overriding the hooks of `Module`, which do nothing by
+//! default. Modules are put in a `Registry`, which `Dorothy` gives the events to, in the order the
+//! modules were added.
+
 use serenity::framework::StandardFramework;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
-pub trait Module {
-    fn register(framework: StandardFramework) -> StandardFramework;
+use std::sync::{Arc, RwLock as StdRwLock};
+
+use confirmation;
+use conversation;
+use paginator;
+
+pub trait Module: Send + Sync {
+    /// Short name of the module.
+    fn name(&self) -> &'static str;
+
+    /// What the module does.
+    fn description(&self) -> &'static str;
+
+    /// Whether servers can disable the module. The ones managing the bot in the servers can't be.
+    fn can_be_disabled(&self) -> bool {
+        true
+    }
+
+    /// Registers the commands of the module.
+    fn register(&self, framework: StandardFramework) -> StandardFramework;
+
+    /// The bot is connected. Called again after reconnections.
+    fn on_ready(&self, _ready: &Ready) {}
+
+    /// The bot is stopping.
+    fn on_shutdown(&self) {}
+
+    /// A message was sent where the bot can see it.
+    fn on_message(&self, _msg: &Message) {}
+
+    /// Someone reacted to a message.
+    fn on_reaction_add(&self, _reaction: &Reaction) {}
+
+    /// A server is available, because the bot just started, joined it or it came back.
+    fn on_guild_create(&self, _guild: &Guild) {}
+
+    /// The bot left a server, or got kicked.
+    fn on_guild_delete(&self, _guild_id: GuildId) {}
+
+    /// A channel of a server was deleted.
+    fn on_channel_delete(&self, _guild_id: GuildId, _channel_id: ChannelId) {}
+
+    /// A role of a server was deleted.
+    fn on_role_delete(&self, _guild_id: GuildId, _role_id: RoleId) {}
+}
+
+/// What's known of a loaded module.
+#[derive(Clone, Copy)]
+pub struct ModuleInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub can_be_disabled: bool,
+}
+
+lazy_static! {
+    static ref LOADED: StdRwLock<Vec<ModuleInfo>> = StdRwLock::new(Vec::new());
+}
+
+/// The loaded modules, in the order they were loaded.
+pub fn loaded_modules() -> Vec<ModuleInfo> {
+    LOADED.read().expect("couldn't lock LOADED for reading").clone()
 }
 
+/// The modules of the bot.
 #[derive(Default)]
-pub struct Dorothy;
+pub struct Registry {
+    modules: Vec<Box<Module>>,
+}
+
+impl Registry {
+    /// Adds a module.
+    pub fn with<M: Module + 'static>(mut self, module: M) -> Self {
+        self.modules.push(Box::new(module));
+        self
+    }
+
+    /// Registers the commands of every module.
+    pub fn register(&self, framework: StandardFramework) -> StandardFramework {
+        self.modules.iter().fold(framework, |framework, module| {
+            info!("Loading module {}: {}", module.name(), module.description());
+            LOADED
+                .write()
+                .expect("couldn't lock LOADED for writing")
+                .push(ModuleInfo {
+                    name: module.name(),
+                    description: module.description(),
+                    can_be_disabled: module.can_be_disabled(),
+                });
+            module.register(framework)
+        })
+    }
+
+    /// Tells every module that the bot is stopping.
+    pub fn shutdown(&self) {
+        for module in &self.modules {
+            module.on_shutdown();
+        }
+    }
+}
+
+pub struct Dorothy {
+    registry: Arc<Registry>,
+}
+
+impl Dorothy {
+    pub fn new(registry: Arc<Registry>) -> Self {
+        Dorothy { registry }
+    }
+
+    fn each<F: Fn(&Module)>(&self, f: F) {
+        for module in &self.registry.modules {
+            f(module.as_ref());
+        }
+    }
+}
 
 impl EventHandler for Dorothy {
-    fn ready(&self, _ctx: Context, _data: Ready) {
+    fn ready(&self, _ctx: Context, data: Ready) {
         info!("NOBODY EXPECTS THE DOROTHINQUISITION!!!");
+        self.each(|m| m.on_ready(&data));
+    }
+
+    fn channel_delete(&self, _ctx: Context, channel: Arc<RwLock<GuildChannel>>) {
+        let (guild_id, channel_id) = {
+            let channel = channel.read();
+            (channel.guild_id, channel.id)
+        };
+        self.each(|m| m.on_channel_delete(guild_id, channel_id));
+    }
+
+    fn guild_create(&self, _ctx: Context, guild: Guild, _is_new: bool) {
+        self.each(|m| m.on_guild_create(&guild));
+    }
+
+    fn guild_role_delete(
+        &self,
+        _ctx: Context,
+        guild_id: GuildId,
+        role_id: RoleId,
+        _role: Option<Role>,
+    ) {
+        self.each(|m| m.on_role_delete(guild_id, role_id));
+    }
+
+    fn guild_delete(
+        &self,
+        _ctx: Context,
+        guild: PartialGuild,
+        _full: Option<Arc<RwLock<Guild>>>,
+    ) {
+        self.each(|m| m.on_guild_delete(guild.id));
+    }
+
+    fn message(&self, _ctx: Context, msg: Message) {
+        conversation::handle_message(&msg);
+        self.each(|m| m.on_message(&msg));
+    }
+
+    fn reaction_add(&self, _ctx: Context, reaction: Reaction) {
+        paginator::handle_reaction(&reaction);
+        confirmation::handle_reaction(&reaction);
+        self.each(|m| m.on_reaction_add(&reaction));
     }
 }
@@ -0,0 +1,162 @@
+//! Errors of the bot.
+//!
+//! Users get a short message in the language of their server, while the full error is logged.
+//! Both carry the same correlation ID, so that a user reporting a problem can be matched with the
+//! logs.
+//!
+//! Commands return `CommandError`, which only holds text. An `Error` turned into a `CommandError`
+//! is kept aside under its correlation ID until `report_command` gets it back, and is reported
+//! under that same ID; any other text is taken as a message meant for the user.
+
+use chrono::Utc;
+
+use serenity::framework::standard::CommandError;
+use serenity::model::misc::Mentionable;
+use serenity::model::prelude::*;
+use serenity::Error as SerenityError;
+
+use serde_json;
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use guild_log::{self, Level};
+use i18n::{tr, tr_with};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Something the user gave is wrong. The message says what, already translated.
+    InvalidArgument(String),
+    /// The server has no configuration.
+    MissingConfig,
+    /// A request to Discord failed.
+    Discord(SerenityError),
+    /// A file couldn't be read or written.
+    Storage(String),
+}
+
+lazy_static! {
+    /// Errors turned into `CommandError`s, by correlation ID.
+    static ref PENDING: Mutex<HashMap<String, Error>> = Mutex::new(HashMap::new());
+}
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A new correlation ID: the time, and a counter for the errors of the same second.
+fn correlation_id() -> String {
+    format!(
+        "{}-{}",
+        Utc::now().format("%Y%m%d%H%M%S"),
+        COUNTER.fetch_add(1, Ordering::Relaxed) % 1000
+    )
+}
+
+impl Error {
+    /// The message shown to the user.
+    pub fn message(&self, guild_id: Option<GuildId>) -> String {
+        match self {
+            Error::InvalidArgument(message) => message.clone(),
+            Error::MissingConfig => tr(guild_id, "pm.get.not_found"),
+            Error::Discord(_) => tr(guild_id, "error.discord"),
+            Error::Storage(_) => tr(guild_id, "error.storage"),
+        }
+    }
+}
+
+impl From<SerenityError> for Error {
+    fn from(error: SerenityError) -> Error {
+        Error::Discord(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Storage(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Error {
+        Error::Storage(error.to_string())
+    }
+}
+
+impl From<Error> for CommandError {
+    fn from(error: Error) -> CommandError {
+        let id = correlation_id();
+        PENDING
+            .lock()
+            .expect("couldn't lock PENDING")
+            .insert(id.clone(), error);
+        CommandError(id)
+    }
+}
+
+impl From<CommandError> for Error {
+    fn from(error: CommandError) -> Error {
+        let pending = PENDING.lock().expect("couldn't lock PENDING").remove(&error.0);
+        pending.unwrap_or(Error::InvalidArgument(error.0))
+    }
+}
+
+/// Logs an error with a new correlation ID, and tells the user about it in a channel.
+pub fn report(guild_id: Option<GuildId>, channel_id: ChannelId, context: &str, error: Error) {
+    report_with_id(correlation_id(), guild_id, channel_id, context, error);
+}
+
+/// Reports the error of a command, under the correlation ID it was given when it became a
+/// `CommandError`.
+pub fn report_command(
+    guild_id: Option<GuildId>,
+    channel_id: ChannelId,
+    context: &str,
+    error: CommandError,
+) {
+    let pending = PENDING.lock().expect("couldn't lock PENDING").remove(&error.0);
+    match pending {
+        Some(pending) => report_with_id(error.0, guild_id, channel_id, context, pending),
+        None => report(guild_id, channel_id, context, Error::InvalidArgument(error.0)),
+    }
+}
+
+fn report_with_id(
+    id: String,
+    guild_id: Option<GuildId>,
+    channel_id: ChannelId,
+    context: &str,
+    error: Error,
+) {
+    warn!("Error {} in {}: {:?}", id, context, error);
+
+    let message = error.message(guild_id);
+    if let Some(guild_id) = guild_id {
+        guild_log::post(
+            guild_id,
+            Level::Error,
+            tr_with(
+                Some(guild_id),
+                "log.error",
+                &[
+                    ("context", context.to_string()),
+                    ("channel", channel_id.mention()),
+                    ("error", message.clone()),
+                    ("id", id.clone()),
+                ],
+            ),
+        );
+    }
+
+    let content = tr_with(
+        guild_id,
+        "error.report",
+        &[("error", message), ("id", id)],
+    );
+    if let Err(e) = channel_id.send_message(|m| m.content(content)) {
+        warn!(
+            "An error has occurred while sending the error message (lol): {:?}",
+            e
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! A log of the bot's activity in a channel of the server, for its admins.
+//!
+//! Servers pick the channel and the lowest level posted with `logchannel`. Configuration commits,
+//! polls opening and closing, messages that couldn't be sent, missing permissions and command
+//! errors are posted there. Servers without a log channel only get them in the bot's own log.
+
+use serenity::framework::standard::{Args, CommandError};
+use serenity::framework::StandardFramework;
+use serenity::model::misc::Mentionable;
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::Colour;
+
+use serde_json::{from_reader, to_writer_pretty};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::RwLock;
+
+use dorothy::Module;
+use error::Error;
+use i18n::{tr, tr_with};
+use modules;
+use utils::{truncate, EMBED_DESCRIPTION_LIMIT};
+
+/// Name of the module.
+const NAME: &str = "log";
+
+/// Importance of an entry. A log channel gets the entries of its level and above.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn parse(text: &str) -> Option<Level> {
+        match text.to_lowercase().as_str() {
+            "info" => Some(Level::Info),
+            "warning" => Some(Level::Warning),
+            "error" => Some(Level::Error),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+
+    fn colour(self) -> Colour {
+        match self {
+            Level::Info => Colour::BLUE,
+            Level::Warning => Colour::ORANGE,
+            Level::Error => Colour::RED,
+        }
+    }
+}
+
+/// Where a server's log goes, and from which level.
+#[derive(Clone, Serialize, Deserialize)]
+struct LogChannel {
+    channel_id: ChannelId,
+    level: Level,
+}
+
+lazy_static! {
+    static ref LOG_CHANNELS: RwLock<HashMap<GuildId, LogChannel>> = {
+        RwLock::new(initialize_log_channels())
+    };
+}
+
+fn initialize_log_channels() -> HashMap<GuildId, LogChannel> {
+    let file = File::open("data/log_channels.json");
+    if file.is_err() {
+        return HashMap::new();
+    }
+    let file = file.unwrap();
+    from_reader(file).unwrap_or_else(|e| {
+        warn!("couldn't deserialize log channels: {:?}", e);
+        HashMap::new()
+    })
+}
+
+fn save_log_channels() -> Result<(), Error> {
+    let file = File::create("data/log_channels.json")?;
+
+    let log_channels = LOG_CHANNELS
+        .read()
+        .expect("couldn't lock LOG_CHANNELS for reading");
+    to_writer_pretty(file, &*log_channels)?;
+    Ok(())
+}
+
+/// Posts an entry in the log channel of a server, if it has one and the level is high enough.
+pub fn post(guild_id: GuildId, level: Level, text: String) {
+    let channel_id = {
+        let log_channels = LOG_CHANNELS
+            .read()
+            .expect("couldn't lock LOG_CHANNELS for reading");
+        match log_channels.get(&guild_id) {
+            Some(log) if level >= log.level => log.channel_id,
+            _ => return,
+        }
+    };
+    if !modules::is_enabled(Some(guild_id), NAME) {
+        return;
+    }
+
+    // An embed, so that the mentions don't ping anyone.
+    let text = truncate(text, EMBED_DESCRIPTION_LIMIT);
+    let result =
+        channel_id.send_message(|m| m.embed(|e| e.description(text).color(level.colour())));
+    // Failing to log isn't logged in the channel, or it'd never end.
+    if let Err(e) = result {
+        warn!(
+            "Couldn't post in the log channel of server {}: {:?}",
+            guild_id, e
+        );
+    }
+}
+
+/// Forgets the log channel of a server the bot isn't in anymore.
+pub fn forget(guild_id: GuildId) {
+    let removed = LOG_CHANNELS
+        .write()
+        .expect("couldn't lock LOG_CHANNELS for writing")
+        .remove(&guild_id)
+        .is_some();
+    if removed {
+        if let Err(e) = save_log_channels() {
+            warn!("Couldn't save log channels: {:?}", e);
+        }
+    }
+}
+
+fn set_log_channel(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    let mut args = args;
+    // Unwrap is safe here because this command is only available in servers.
+    let server_id = msg.guild_id.unwrap();
+
+    if args.is_empty() {
+        let content = {
+            let log_channels = LOG_CHANNELS
+                .read()
+                .expect("couldn't lock LOG_CHANNELS for reading");
+            match log_channels.get(&server_id) {
+                Some(log) => tr_with(
+                    Some(server_id),
+                    "log.current",
+                    &[
+                        ("channel", log.channel_id.mention()),
+                        ("level", log.level.name().to_string()),
+                    ],
+                ),
+                None => tr(Some(server_id), "log.none"),
+            }
+        };
+        msg.channel_id.send_message(|m| m.content(content))?;
+        return Ok(());
+    }
+
+    let first = args.single::<String>()?;
+    if first == "off" {
+        LOG_CHANNELS
+            .write()
+            .expect("couldn't lock LOG_CHANNELS for writing")
+            .remove(&server_id);
+        save_log_channels()?;
+        msg.channel_id
+            .send_message(|m| m.content(tr(Some(server_id), "log.off")))?;
+        return Ok(());
+    }
+
+    let channel_id = first
+        .parse::<ChannelId>()
+        .ok()
+        .filter(|c| match c.to_channel_cached() {
+            Some(Channel::Guild(ref c)) => c.read().guild_id == server_id,
+            _ => false,
+        }).ok_or_else(|| Error::InvalidArgument(tr(Some(server_id), "log.bad_channel")))?;
+    let level = match args.single::<String>() {
+        Ok(level) => Level::parse(&level).ok_or_else(|| {
+            Error::InvalidArgument(tr_with(
+                Some(server_id),
+                "log.bad_level",
+                &[("level", level)],
+            ))
+        })?,
+        Err(_) => Level::Info,
+    };
+
+    LOG_CHANNELS
+        .write()
+        .expect("couldn't lock LOG_CHANNELS for writing")
+        .insert(server_id, LogChannel { channel_id, level });
+    save_log_channels()?;
+
+    msg.channel_id.send_message(|m| {
+        m.content(tr_with(
+            Some(server_id),
+            "log.set",
+            &[
+                ("channel", channel_id.mention()),
+                ("level", level.name().to_string()),
+            ],
+        ))
+    })?;
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct GuildLog;
+
+impl Module for GuildLog {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Log of the bot's activity in the server"
+    }
+
+    fn register(&self, framework: StandardFramework) -> StandardFramework {
+        framework.group("Log", |g| {
+            modules::group(
+                self.name(),
+                Permissions::MANAGE_GUILD,
+                g.desc("Log of the bot's activity in the server")
+                    .command("logchannel", |c| {
+                        c.desc("Shows, sets or turns off the channel where I post what I do in this server, from the given level (info, warning or error).")
+                            .usage("[#channel [level] | off]")
+                            .max_args(2)
+                            .guild_only(true)
+                            .exec(set_log_channel)
+                    }),
+            )
+        })
+    }
+
+    fn on_guild_delete(&self, guild_id: GuildId) {
+        forget(guild_id);
+    }
+}
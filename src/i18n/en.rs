@@ -0,0 +1,261 @@
+//! English messages. This is the reference catalog: every other language must have the same keys.
+
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Common
+    ("cont", "(cont)"),
+    ("none", "None"),
+    ("yes", "Yes"),
+    ("no", "No"),
+    ("error", "An error has occurred: `{error}`"),
+    ("error.report", "{error} (error ID: `{id}`)"),
+    ("error.discord", "Discord didn't let me do that, please try again later."),
+    ("error.storage", "I couldn't save or load my data."),
+    // Localization
+    ("i18n.current", "The language is `{language}`. Available languages: {available}."),
+    ("i18n.unknown", "I don't speak `{language}`."),
+    ("i18n.set", "Language changed."),
+    // Pagination
+    ("paginator.page", "Page {page}/{pages}"),
+    // Commands that couldn't be run
+    ("dispatch.usage", "Usage: `{usage}`"),
+    ("dispatch.not_enough_arguments", "This command needs at least {min} arguments, but got {given}."),
+    ("dispatch.too_many_arguments", "This command takes at most {max} arguments, but got {given}."),
+    ("dispatch.permissions", "You need these permissions to use this command: {permissions}."),
+    ("dispatch.owners_only", "Only the owners of the bot can use this command."),
+    ("dispatch.rate_limited", "Slow down! You can use this command again in {seconds} seconds."),
+    ("dispatch.blocked_user", "You're not allowed to use my commands."),
+    ("dispatch.blocked_guild", "My commands can't be used in this server."),
+    ("dispatch.blocked_channel", "My commands can't be used in this channel."),
+    ("dispatch.disabled", "The command `{command}` is disabled."),
+    ("dispatch.guild_only", "This command can only be used in a server."),
+    ("dispatch.dm_only", "This command can only be used in private messages."),
+    ("dispatch.lacking_role", "You don't have the role needed for this command."),
+    ("dispatch.check_failed", "You can't use this command here."),
+    // Log channel
+    ("log.current", "My log goes to {channel}, from the `{level}` level."),
+    ("log.none", "This server has no log channel."),
+    ("log.off", "Log channel turned off."),
+    ("log.set", "My log now goes to {channel}, from the `{level}` level."),
+    ("log.bad_channel", "Please give a channel of this server."),
+    ("log.bad_level", "Unknown level `{level}`, use `info`, `warning` or `error`."),
+    ("log.error", "Error `{id}` in {context} ({channel}): {error}"),
+    ("log.revision", "Configuration revision {number} saved by {author}.\n{summary}"),
+    ("log.poll_opened", "Poll opened in {channel} with {count} games."),
+    ("log.poll_skipped", "Poll skipped: {skip}."),
+    ("log.poll_closed", "Poll closed, {count} games got players."),
+    ("log.poll_lost", "The poll closed, but I don't know its messages anymore (was I restarted?)."),
+    ("log.poll_message_missing", "The poll message {message} couldn't be read, its answers are lost."),
+    ("log.send_failed", "I couldn't send a message in {channel}, please check my permissions there."),
+    // Modules
+    ("modules.list", "Modules of this server:"),
+    ("modules.enabled", "enabled"),
+    ("modules.disabled", "disabled"),
+    ("modules.always_enabled", "always enabled"),
+    ("modules.unknown", "There's no module named `{module}`, see `modules list`."),
+    ("modules.locked", "This module can't be disabled."),
+    ("modules.enable.done", "Module `{module}` enabled."),
+    ("modules.disable.done", "Module `{module}` disabled."),
+    // Command permissions
+    ("perms.list", "Allowed on top of the default permissions:"),
+    ("perms.none", "Only the default permissions apply in this server."),
+    ("perms.no_target", "Please give a command (in quotes if it has several words) or a module."),
+    ("perms.unknown", "There's no command or module named `{target}`."),
+    ("perms.locked", "The commands of this module can't be given away."),
+    ("perms.no_mentions", "Please mention the roles and users."),
+    ("perms.changed", "`{target}` can now be used by {allowed}, on top of the default permissions."),
+    ("perms.default", "`{target}` is back to the default permissions."),
+    // Cooldowns
+    ("cooldowns.unknown", "There's no command named `{command}`."),
+    ("cooldowns.bad_seconds", "Please give the cooldowns in seconds, or `default`."),
+    ("cooldowns.current", "After using `{command}`, a user waits {user} seconds, and the server {guild} seconds."),
+    ("cooldowns.set", "From now on, after using `{command}`, a user waits {user} seconds, and the server {guild} seconds."),
+    // Blocklist
+    ("blocklist.bad_user", "Please mention the user, or give their ID."),
+    ("blocklist.bad_guild", "Please give the ID of the server."),
+    ("blocklist.owner", "Owners of the bot can't be blocked."),
+    ("blocklist.bad_duration", "`{duration}` isn't a usable duration, leave it out to block until unblocked."),
+    ("blocklist.until", "until {time}"),
+    ("blocklist.forever", "until unblocked"),
+    ("blocklist.user_blocked", "{user} is blocked {block}."),
+    ("blocklist.guild_blocked", "The server {guild} is blocked {block}."),
+    ("blocklist.user_unblocked", "{user} isn't blocked anymore."),
+    ("blocklist.user_not_blocked", "{user} isn't blocked."),
+    ("blocklist.guild_unblocked", "The server {guild} isn't blocked anymore."),
+    ("blocklist.guild_not_blocked", "The server {guild} isn't blocked."),
+    ("blocklist.empty", "Nobody is blocked."),
+    ("blocklist.users", "Blocked users"),
+    ("blocklist.guilds", "Blocked servers"),
+    // Misc
+    ("misc.id.no_roles", "please mention roles when asking for role IDs!"),
+    ("misc.id.no_users", "please mention users when asking for user Ids!"),
+    ("misc.id.roles", "Role IDs"),
+    ("misc.id.users", "User IDs"),
+    ("misc.id.unknown", "I don't understand."),
+    // Premade creator configuration
+    ("pm.no_draft", "No configuration is being edited, use get or create first."),
+    ("pm.get.not_found", "Couldn't find server in config file"),
+    ("pm.get.title", "Server configuration loaded!"),
+    ("pm.create.args", "The Create command takes 3 arguments"),
+    ("pm.create.title", "New configuration created!"),
+    ("pm.set.title", "Server configuration modified (don't forget to commit it)"),
+    ("pm.roles.title", "Roles set (don't forget to commit)"),
+    ("pm.game.title", "Game added (don't forget to commit)"),
+    ("pm.commit.done", "Configuration saved and written to disk."),
+    ("pm.commit.confirm", "React with ✅ to save these changes."),
+    ("pm.diff.title", "Changes"),
+    ("pm.diff.none", "No changes."),
+    ("pm.diff.settings", "Settings"),
+    ("pm.diff.games", "Games"),
+    ("pm.diff.templates", "Templates"),
+    ("pm.diff.channel", "Channel: {old} → {new}"),
+    ("pm.diff.start", "Start: `{old}` → `{new}`"),
+    ("pm.diff.end", "End: `{old}` → `{new}`"),
+    ("pm.diff.role_added", "+ role {role}"),
+    ("pm.diff.role_removed", "- role {role}"),
+    ("pm.diff.game_added", "+ {game}"),
+    ("pm.diff.game_removed", "- {game}"),
+    ("pm.diff.game_changed", "~ {game}: {changes}"),
+    ("pm.diff.game.emoji", "emoji {old} → {new}"),
+    ("pm.diff.game.channel", "channel {old} → {new}"),
+    ("pm.diff.game.roles", "roles {old} → {new}"),
+    ("pm.diff.poll_template", "Poll template modified"),
+    ("pm.diff.result_template", "Result template modified"),
+    ("pm.history.title", "Configuration history"),
+    ("pm.history.empty", "No configuration was committed yet."),
+    ("pm.history.revision", "Revision {number}, {date}"),
+    ("pm.history.author", "By {author}"),
+    ("pm.history.not_found", "there's no revision {number}"),
+    ("pm.history.rollback", "Rollback to revision {number}"),
+    ("pm.history.restored", "Revision {number} restored, as revision {revision}."),
+    ("pm.export.done", "Here's the configuration of this server."),
+    ("pm.import.no_file", "please attach the configuration file to the command"),
+    ("pm.import.too_big", "the file is too big to be a configuration"),
+    ("pm.import.invalid", "the file isn't a valid configuration: {error}"),
+    ("pm.import.which", "the file doesn't have a configuration for this server"),
+    ("pm.import.duplicate_game", "the game `{game}` is there twice"),
+    ("pm.import.duplicate_emoji", "the emoji {emoji} is used by two games"),
+    ("pm.import.title", "Configuration imported (check it with diff, then commit it)"),
+    (
+        "pm.wizard.start",
+        "Let's set up the polls! Answer my questions here, or say `cancel` to stop.",
+    ),
+    ("pm.wizard.channel", "In which channel should the polls be posted?"),
+    (
+        "pm.wizard.days",
+        "On which days? Answer `every day`, `weekdays`, `weekends` or days like `mon, wed, fri`.",
+    ),
+    ("pm.wizard.start_time", "At what time should the poll start? Like `19:00` (UTC)."),
+    ("pm.wizard.end_time", "At what time should the poll close? An earlier time than the start is on the next day."),
+    (
+        "pm.wizard.roles",
+        "Which roles should be mentioned by the poll? Mention them, or answer `everyone` or \
+         `none`.",
+    ),
+    (
+        "pm.wizard.game",
+        "Now the games, one by one: `name emoji #channel [@role...]` (put names with spaces in \
+         quotes).",
+    ),
+    ("pm.wizard.game_added", "{game} added. Next game, or `done`?"),
+    ("pm.wizard.bad_channel", "I can't find this channel in this server."),
+    ("pm.wizard.bad_days", "I don't understand these days."),
+    ("pm.wizard.bad_time", "Please give a time like `19:00`."),
+    ("pm.wizard.end_at_start", "The poll can't close at the time it starts."),
+    ("pm.wizard.bad_roles", "Please mention roles, or answer `everyone` or `none`."),
+    ("pm.wizard.bad_game", "Please answer like `\"Rocket League\" 🏎 #rocket-league`."),
+    ("pm.wizard.no_games", "Add at least one game first."),
+    ("pm.wizard.cancelled", "Setup cancelled."),
+    ("pm.wizard.done", "Setup finished (check it with diff, then commit it)"),
+    ("pm.discard.done", "Your changes were thrown away."),
+    (
+        "pm.draft.others",
+        "{users} are also editing this configuration: the last one to commit will overwrite \
+         the others' changes.",
+    ),
+    ("pm.rehash.done", "Configuration successfully reloaded."),
+    ("pm.next_events", "Next poll: {start}, closing {end}."),
+    ("pm.template.kind", "the template is either `poll` or `result`"),
+    ("pm.template.no_game", "Add a game first to preview the results."),
+    ("pm.template.preview", "Preview:"),
+    ("pm.template.title", "Template modified (don't forget to commit). Preview:"),
+    ("pm.display.channel", "Channel"),
+    ("pm.display.times", "Event times"),
+    ("pm.display.times_value", "Starts: {start}\n  Ends: {end}\n"),
+    ("pm.display.roles", "Roles"),
+    ("pm.display.game", "In {channel}, {roles}"),
+    ("pm.display.skipped", "Skipped"),
+    ("pm.display.next", "Next polls"),
+    (
+        "pm.display.split",
+        "There are {count} games but a message can only have {max} reactions, \
+         so the poll will be split in {messages} messages.",
+    ),
+    ("template.bad_colour", "`{value}` isn't a hexadecimal colour, from `000000` to `FFFFFF`"),
+    ("template.bad_url", "`{value}` isn't a web address (`https://...`)"),
+    ("template.bad_part", "`{part}` isn't a part of a template"),
+    // Configuration checks
+    ("check.title", "Configuration problems"),
+    ("check.problems", "Problems"),
+    ("check.ok", "No problems found in the configuration."),
+    ("check.no_guild", "I can't see the server right now."),
+    ("check.channel_missing", "The channel {channel} doesn't exist anymore."),
+    ("check.permissions", "I'm missing permissions in {channel}: {permissions}."),
+    ("check.role_missing", "The role {role} doesn't exist anymore."),
+    ("check.emoji_missing", "The emoji of {game} doesn't exist in the server anymore."),
+    ("check.status.title", "Permission checks"),
+    ("check.status.checked", "Checked {time}."),
+    ("check.status.ok", "I have all the permissions I need."),
+    ("check.status.none", "No server has been checked yet."),
+    // Configuration events
+    ("events.title", "Configuration updated"),
+    ("events.changes", "Changes"),
+    ("events.role_deleted", "The role {role} was deleted."),
+    ("events.channel_deleted", "The channel {channel} was deleted."),
+    (
+        "events.polls_channel_deleted",
+        "The channel of the polls was deleted: no poll will be posted until another channel is \
+         set with `pmconfig set`.",
+    ),
+    ("events.guild_deleted", "I'm not in server {server} anymore, its configuration was removed."),
+    // Schedules
+    ("schedule.invalid", "invalid schedule `{schedule}`: {error}"),
+    ("schedule.bad_duration", "invalid duration `{duration}`, expected something like `+3h`"),
+    ("schedule.never", "these polls would never happen"),
+    (
+        "schedule.too_often",
+        "the polls of {start} and {next} are too close, there must be at least {interval} \
+         minutes between two polls",
+    ),
+    ("schedule.no_end", "the poll of {start} doesn't end before the next one starts"),
+    (
+        "schedule.relative_start",
+        "the end can only be relative to a start like `weekdays at 19:00`",
+    ),
+    // Skips
+    ("skip.announced", "Announced"),
+    ("skip.bad_date", "invalid date `{date}`, expected YYYY-MM-DD"),
+    ("skip.bad_range", "the end of the range is before its start"),
+    ("skip.no_poll", "couldn't find any upcoming poll"),
+    ("skip.announce.on_off", "please answer with `on` or `off`"),
+    ("skip.import.path", "please give a file name, not a path"),
+    ("skip.import.error", "couldn't read the calendar: {error}"),
+    ("skip.list.title", "Skipped polls"),
+    ("skip.clear.title", "Skips cleared"),
+    ("skip.announce.title", "Announcement setting saved"),
+    ("skip.import.title", "Calendar imported"),
+    ("skip.next.title", "Next poll skipped"),
+    ("skip.dates.title", "Dates skipped"),
+    // Polls
+    ("poll.title", "Pick your games!"),
+    (
+        "poll.description",
+        "Today, these following games are available!\n\
+         React with the corresponding emoji to say you're available!\n\
+         I will send messages with all the participants in their respective games' channels.\n",
+    ),
+    ("poll.games", "Games"),
+    ("poll.skipped.title", "No poll today!"),
+    ("poll.skipped.description", "Polls are skipped: {skip}"),
+    ("result.title", "Today's players"),
+    ("result.description", "The following players want to play:"),
+];
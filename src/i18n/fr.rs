@@ -0,0 +1,265 @@
+//! Messages en français.
+
+pub const MESSAGES: &[(&str, &str)] = &[
+    // Common
+    ("cont", "(suite)"),
+    ("none", "Aucun"),
+    ("yes", "Oui"),
+    ("no", "Non"),
+    ("error", "Une erreur est survenue : `{error}`"),
+    ("error.report", "{error} (identifiant de l'erreur : `{id}`)"),
+    ("error.discord", "Discord ne m'a pas laissé faire, réessayez plus tard."),
+    ("error.storage", "Je n'ai pas pu enregistrer ou charger mes données."),
+    // Localization
+    ("i18n.current", "La langue est `{language}`. Langues disponibles : {available}."),
+    ("i18n.unknown", "Je ne parle pas `{language}`."),
+    ("i18n.set", "Langue modifiée."),
+    // Pagination
+    ("paginator.page", "Page {page}/{pages}"),
+    // Commandes qui n'ont pas pu être lancées
+    ("dispatch.usage", "Utilisation : `{usage}`"),
+    ("dispatch.not_enough_arguments", "Cette commande a besoin d'au moins {min} arguments, mais en a reçu {given}."),
+    ("dispatch.too_many_arguments", "Cette commande prend au plus {max} arguments, mais en a reçu {given}."),
+    ("dispatch.permissions", "Vous avez besoin de ces permissions pour utiliser cette commande : {permissions}."),
+    ("dispatch.owners_only", "Seuls les propriétaires du bot peuvent utiliser cette commande."),
+    ("dispatch.rate_limited", "Doucement ! Vous pourrez réutiliser cette commande dans {seconds} secondes."),
+    ("dispatch.blocked_user", "Vous n'avez pas le droit d'utiliser mes commandes."),
+    ("dispatch.blocked_guild", "Mes commandes ne peuvent pas être utilisées sur ce serveur."),
+    ("dispatch.blocked_channel", "Mes commandes ne peuvent pas être utilisées dans ce salon."),
+    ("dispatch.disabled", "La commande `{command}` est désactivée."),
+    ("dispatch.guild_only", "Cette commande ne peut être utilisée que sur un serveur."),
+    ("dispatch.dm_only", "Cette commande ne peut être utilisée qu'en message privé."),
+    ("dispatch.lacking_role", "Vous n'avez pas le rôle nécessaire pour cette commande."),
+    ("dispatch.check_failed", "Vous ne pouvez pas utiliser cette commande ici."),
+    // Salon de journal
+    ("log.current", "Mon journal va dans {channel}, à partir du niveau `{level}`."),
+    ("log.none", "Ce serveur n'a pas de salon de journal."),
+    ("log.off", "Salon de journal désactivé."),
+    ("log.set", "Mon journal va maintenant dans {channel}, à partir du niveau `{level}`."),
+    ("log.bad_channel", "Veuillez donner un salon de ce serveur."),
+    ("log.bad_level", "Niveau `{level}` inconnu, utilisez `info`, `warning` ou `error`."),
+    ("log.error", "Erreur `{id}` dans {context} ({channel}) : {error}"),
+    ("log.revision", "Révision {number} de la configuration enregistrée par {author}.\n{summary}"),
+    ("log.poll_opened", "Sondage ouvert dans {channel} avec {count} jeux."),
+    ("log.poll_skipped", "Sondage sauté : {skip}."),
+    ("log.poll_closed", "Sondage fermé, {count} jeux ont des joueurs."),
+    ("log.poll_lost", "Le sondage est fermé, mais je ne connais plus ses messages (ai-je redémarré ?)."),
+    ("log.poll_message_missing", "Le message de sondage {message} n'a pas pu être lu, ses réponses sont perdues."),
+    ("log.send_failed", "Je n'ai pas pu envoyer de message dans {channel}, veuillez vérifier mes permissions."),
+    // Modules
+    ("modules.list", "Modules de ce serveur :"),
+    ("modules.enabled", "activé"),
+    ("modules.disabled", "désactivé"),
+    ("modules.always_enabled", "toujours activé"),
+    ("modules.unknown", "Il n'y a pas de module nommé `{module}`, voir `modules list`."),
+    ("modules.locked", "Ce module ne peut pas être désactivé."),
+    ("modules.enable.done", "Module `{module}` activé."),
+    ("modules.disable.done", "Module `{module}` désactivé."),
+    // Permissions des commandes
+    ("perms.list", "Autorisés en plus des permissions par défaut :"),
+    ("perms.none", "Seules les permissions par défaut s'appliquent sur ce serveur."),
+    ("perms.no_target", "Veuillez donner une commande (entre guillemets si elle a plusieurs mots) ou un module."),
+    ("perms.unknown", "Il n'y a pas de commande ou de module nommé `{target}`."),
+    ("perms.locked", "Les commandes de ce module ne peuvent pas être confiées à d'autres."),
+    ("perms.no_mentions", "Veuillez mentionner les rôles et les utilisateurs."),
+    ("perms.changed", "`{target}` peut maintenant être utilisé par {allowed}, en plus des permissions par défaut."),
+    ("perms.default", "`{target}` revient aux permissions par défaut."),
+    // Temps de recharge
+    ("cooldowns.unknown", "Il n'y a pas de commande nommée `{command}`."),
+    ("cooldowns.bad_seconds", "Veuillez donner les temps de recharge en secondes, ou `default`."),
+    ("cooldowns.current", "Après avoir utilisé `{command}`, un utilisateur attend {user} secondes, et le serveur {guild} secondes."),
+    ("cooldowns.set", "Désormais, après avoir utilisé `{command}`, un utilisateur attend {user} secondes, et le serveur {guild} secondes."),
+    // Liste de blocage
+    ("blocklist.bad_user", "Veuillez mentionner l'utilisateur, ou donner son ID."),
+    ("blocklist.bad_guild", "Veuillez donner l'ID du serveur."),
+    ("blocklist.owner", "Les propriétaires du bot ne peuvent pas être bloqués."),
+    ("blocklist.bad_duration", "`{duration}` n'est pas une durée utilisable, retirez-la pour bloquer jusqu'au déblocage."),
+    ("blocklist.until", "jusqu'au {time}"),
+    ("blocklist.forever", "jusqu'à nouvel ordre"),
+    ("blocklist.user_blocked", "{user} est bloqué {block}."),
+    ("blocklist.guild_blocked", "Le serveur {guild} est bloqué {block}."),
+    ("blocklist.user_unblocked", "{user} n'est plus bloqué."),
+    ("blocklist.user_not_blocked", "{user} n'est pas bloqué."),
+    ("blocklist.guild_unblocked", "Le serveur {guild} n'est plus bloqué."),
+    ("blocklist.guild_not_blocked", "Le serveur {guild} n'est pas bloqué."),
+    ("blocklist.empty", "Personne n'est bloqué."),
+    ("blocklist.users", "Utilisateurs bloqués"),
+    ("blocklist.guilds", "Serveurs bloqués"),
+    // Misc
+    ("misc.id.no_roles", "mentionne des rôles pour avoir leurs IDs !"),
+    ("misc.id.no_users", "mentionne des utilisateurs pour avoir leurs IDs !"),
+    ("misc.id.roles", "IDs des rôles"),
+    ("misc.id.users", "IDs des utilisateurs"),
+    ("misc.id.unknown", "Je ne comprends pas."),
+    // Premade creator configuration
+    ("pm.no_draft", "Aucune configuration en cours de modification, utilise get ou create d'abord."),
+    ("pm.get.not_found", "Ce serveur n'est pas dans la configuration"),
+    ("pm.get.title", "Configuration du serveur chargée !"),
+    ("pm.create.args", "La commande create prend 3 arguments"),
+    ("pm.create.title", "Nouvelle configuration créée !"),
+    ("pm.set.title", "Configuration du serveur modifiée (n'oublie pas de la valider)"),
+    ("pm.roles.title", "Rôles ajoutés (n'oublie pas de valider)"),
+    ("pm.game.title", "Jeu ajouté (n'oublie pas de valider)"),
+    ("pm.commit.done", "Configuration enregistrée et écrite sur le disque."),
+    ("pm.commit.confirm", "Réagis avec ✅ pour enregistrer ces modifications."),
+    ("pm.diff.title", "Modifications"),
+    ("pm.diff.none", "Aucune modification."),
+    ("pm.diff.settings", "Paramètres"),
+    ("pm.diff.games", "Jeux"),
+    ("pm.diff.templates", "Modèles"),
+    ("pm.diff.channel", "Salon : {old} → {new}"),
+    ("pm.diff.start", "Début : `{old}` → `{new}`"),
+    ("pm.diff.end", "Fin : `{old}` → `{new}`"),
+    ("pm.diff.role_added", "+ rôle {role}"),
+    ("pm.diff.role_removed", "- rôle {role}"),
+    ("pm.diff.game_added", "+ {game}"),
+    ("pm.diff.game_removed", "- {game}"),
+    ("pm.diff.game_changed", "~ {game} : {changes}"),
+    ("pm.diff.game.emoji", "emoji {old} → {new}"),
+    ("pm.diff.game.channel", "salon {old} → {new}"),
+    ("pm.diff.game.roles", "rôles {old} → {new}"),
+    ("pm.diff.poll_template", "Modèle du sondage modifié"),
+    ("pm.diff.result_template", "Modèle des résultats modifié"),
+    ("pm.history.title", "Historique de la configuration"),
+    ("pm.history.empty", "Aucune configuration n'a encore été enregistrée."),
+    ("pm.history.revision", "Révision {number}, {date}"),
+    ("pm.history.author", "Par {author}"),
+    ("pm.history.not_found", "il n'y a pas de révision {number}"),
+    ("pm.history.rollback", "Retour à la révision {number}"),
+    ("pm.history.restored", "Révision {number} restaurée, en tant que révision {revision}."),
+    ("pm.export.done", "Voici la configuration de ce serveur."),
+    ("pm.import.no_file", "joins le fichier de configuration à la commande"),
+    ("pm.import.too_big", "le fichier est trop gros pour être une configuration"),
+    ("pm.import.invalid", "le fichier n'est pas une configuration valide : {error}"),
+    ("pm.import.which", "le fichier n'a pas de configuration pour ce serveur"),
+    ("pm.import.duplicate_game", "le jeu `{game}` est présent deux fois"),
+    ("pm.import.duplicate_emoji", "l'emoji {emoji} est utilisé par deux jeux"),
+    ("pm.import.title", "Configuration importée (vérifie-la avec diff, puis valide-la avec commit)"),
+    (
+        "pm.wizard.start",
+        "Configurons les sondages ! Réponds à mes questions ici, ou dis `cancel` pour arrêter.",
+    ),
+    ("pm.wizard.channel", "Dans quel salon faut-il poster les sondages ?"),
+    (
+        "pm.wizard.days",
+        "Quels jours ? Réponds `every day` (tous les jours), `weekdays` (en semaine), `weekends` \
+         (le week-end) ou des jours comme `mon, wed, fri`.",
+    ),
+    ("pm.wizard.start_time", "À quelle heure le sondage commence-t-il ? Par exemple `19:00` (UTC)."),
+    ("pm.wizard.end_time", "À quelle heure le sondage se termine-t-il ? Une heure plus tôt que le début est le lendemain."),
+    (
+        "pm.wizard.roles",
+        "Quels rôles le sondage doit-il mentionner ? Mentionne-les, ou réponds `everyone` (tout le \
+         monde) ou `none` (personne).",
+    ),
+    (
+        "pm.wizard.game",
+        "Maintenant les jeux, un par un : `nom emoji #salon [@rôle...]` (mets les noms avec des \
+         espaces entre guillemets).",
+    ),
+    ("pm.wizard.game_added", "{game} ajouté. Jeu suivant, ou `done` ?"),
+    ("pm.wizard.bad_channel", "Je ne trouve pas ce salon dans ce serveur."),
+    ("pm.wizard.bad_days", "Je ne comprends pas ces jours."),
+    ("pm.wizard.bad_time", "Donne une heure comme `19:00`."),
+    ("pm.wizard.end_at_start", "Le sondage ne peut pas se terminer à l'heure où il commence."),
+    ("pm.wizard.bad_roles", "Mentionne des rôles, ou réponds `everyone` ou `none`."),
+    ("pm.wizard.bad_game", "Réponds comme `\"Rocket League\" 🏎 #rocket-league`."),
+    ("pm.wizard.no_games", "Ajoute d'abord au moins un jeu."),
+    ("pm.wizard.cancelled", "Configuration annulée."),
+    ("pm.wizard.done", "Configuration terminée (vérifie-la avec diff, puis valide-la avec commit)"),
+    ("pm.discard.done", "Tes modifications ont été abandonnées."),
+    (
+        "pm.draft.others",
+        "{users} modifient aussi cette configuration : le dernier à la valider écrasera les \
+         modifications des autres.",
+    ),
+    ("pm.rehash.done", "Configuration rechargée."),
+    ("pm.next_events", "Prochain sondage : {start}, clôture {end}."),
+    ("pm.template.kind", "le modèle est soit `poll` soit `result`"),
+    ("pm.template.no_game", "Ajoute d'abord un jeu pour prévisualiser les résultats."),
+    ("pm.template.preview", "Aperçu :"),
+    ("pm.template.title", "Modèle modifié (n'oublie pas de valider). Aperçu :"),
+    ("pm.display.channel", "Salon"),
+    ("pm.display.times", "Horaires"),
+    ("pm.display.times_value", "Début : {start}\n  Fin : {end}\n"),
+    ("pm.display.roles", "Rôles"),
+    ("pm.display.game", "Dans {channel}, {roles}"),
+    ("pm.display.skipped", "Annulés"),
+    ("pm.display.next", "Prochains sondages"),
+    (
+        "pm.display.split",
+        "Il y a {count} jeux mais un message ne peut avoir que {max} réactions, \
+         le sondage sera donc réparti sur {messages} messages.",
+    ),
+    ("template.bad_colour", "`{value}` n'est pas une couleur hexadécimale, de `000000` à `FFFFFF`"),
+    ("template.bad_url", "`{value}` n'est pas une adresse web (`https://...`)"),
+    ("template.bad_part", "`{part}` ne fait pas partie d'un modèle"),
+    // Vérifications de la configuration
+    ("check.title", "Problèmes de configuration"),
+    ("check.problems", "Problèmes"),
+    ("check.ok", "Aucun problème trouvé dans la configuration."),
+    ("check.no_guild", "Je ne vois pas le serveur pour le moment."),
+    ("check.channel_missing", "Le salon {channel} n'existe plus."),
+    ("check.permissions", "Il me manque des permissions dans {channel} : {permissions}."),
+    ("check.role_missing", "Le rôle {role} n'existe plus."),
+    ("check.emoji_missing", "L'emoji de {game} n'existe plus dans le serveur."),
+    ("check.status.title", "Vérifications des permissions"),
+    ("check.status.checked", "Vérifié le {time}."),
+    ("check.status.ok", "J'ai toutes les permissions dont j'ai besoin."),
+    ("check.status.none", "Aucun serveur n'a encore été vérifié."),
+    // Événements de la configuration
+    ("events.title", "Configuration mise à jour"),
+    ("events.changes", "Modifications"),
+    ("events.role_deleted", "Le rôle {role} a été supprimé."),
+    ("events.channel_deleted", "Le salon {channel} a été supprimé."),
+    (
+        "events.polls_channel_deleted",
+        "Le salon des sondages a été supprimé : aucun sondage ne sera posté tant qu'un autre salon \
+         n'est pas choisi avec `pmconfig set`.",
+    ),
+    (
+        "events.guild_deleted",
+        "Je ne suis plus dans le serveur {server}, sa configuration a été supprimée.",
+    ),
+    // Horaires
+    ("schedule.invalid", "horaire `{schedule}` invalide : {error}"),
+    ("schedule.bad_duration", "durée `{duration}` invalide, il faut quelque chose comme `+3h`"),
+    ("schedule.never", "ces sondages n'auraient jamais lieu"),
+    (
+        "schedule.too_often",
+        "les sondages du {start} et du {next} sont trop proches, il faut au moins {interval} \
+         minutes entre deux sondages",
+    ),
+    ("schedule.no_end", "le sondage du {start} ne se termine pas avant le début du suivant"),
+    (
+        "schedule.relative_start",
+        "la fin ne peut être relative qu'à un début comme `weekdays at 19:00`",
+    ),
+    // Skips
+    ("skip.announced", "Annoncés"),
+    ("skip.bad_date", "date `{date}` invalide, format attendu : AAAA-MM-JJ"),
+    ("skip.bad_range", "la fin de la période est avant son début"),
+    ("skip.no_poll", "aucun sondage à venir"),
+    ("skip.announce.on_off", "réponds par `on` ou `off`"),
+    ("skip.import.path", "donne un nom de fichier, pas un chemin"),
+    ("skip.import.error", "impossible de lire le calendrier : {error}"),
+    ("skip.list.title", "Sondages annulés"),
+    ("skip.clear.title", "Annulations effacées"),
+    ("skip.announce.title", "Réglage des annonces enregistré"),
+    ("skip.import.title", "Calendrier importé"),
+    ("skip.next.title", "Prochain sondage annulé"),
+    ("skip.dates.title", "Dates annulées"),
+    // Polls
+    ("poll.title", "Choisissez vos jeux !"),
+    (
+        "poll.description",
+        "Aujourd'hui, ces jeux sont disponibles !\n\
+         Réagissez avec l'emoji correspondant pour dire que vous êtes disponibles !\n\
+         J'enverrai la liste des participants dans le salon de chaque jeu.\n",
+    ),
+    ("poll.games", "Jeux"),
+    ("poll.skipped.title", "Pas de sondage aujourd'hui !"),
+    ("poll.skipped.description", "Les sondages sont annulés : {skip}"),
+    ("result.title", "Les joueurs du jour"),
+    ("result.description", "Ces joueurs veulent jouer :"),
+];
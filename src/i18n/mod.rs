@@ -0,0 +1,329 @@
+//! Localization of the messages sent by the bot.
+//!
+//! Each language has a catalog of messages indexed by keys (see `en.rs` for the reference
+//! catalog). Servers pick their language with the `language` command; servers that didn't, and
+//! private messages, use the `language` setting, or English if it's not set.
+//! Messages can contain `{name}` placeholders, filled with `tr_with`.
+//!
+//! The descriptions and usages of the commands, shown by `help`, aren't localized: the framework
+//! registers them once for every server, so they stay in English.
+
+use serenity::framework::standard::{Args, CommandError};
+use serenity::framework::StandardFramework;
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use serde_json::{from_reader, to_writer_pretty};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::RwLock;
+
+use dorothy::Module;
+use error::Error;
+use modules;
+use utils::fill_placeholders;
+use SETTINGS;
+
+mod en;
+mod fr;
+
+/// Every shipped language, with its catalog.
+const CATALOGS: &[(&str, &[(&str, &str)])] = &[("en", en::MESSAGES), ("fr", fr::MESSAGES)];
+
+/// Language used when the configured fallback is unknown.
+const REFERENCE_LANGUAGE: &str = "en";
+
+lazy_static! {
+    static ref LANGUAGES: RwLock<HashMap<GuildId, String>> = {
+        RwLock::new(initialize_languages())
+    };
+}
+
+fn initialize_languages() -> HashMap<GuildId, String> {
+    let file = File::open("data/languages.json");
+    if file.is_err() {
+        return HashMap::new();
+    }
+    let file = file.unwrap();
+    from_reader(file).unwrap_or_else(|e| {
+        warn!("couldn't deserialize languages: {:?}", e);
+        HashMap::new()
+    })
+}
+
+fn save_languages() -> Result<(), Error> {
+    let file = File::create("data/languages.json")?;
+
+    let languages = LANGUAGES
+        .read()
+        .expect("couldn't lock LANGUAGES for reading");
+    to_writer_pretty(file, &*languages)?;
+    Ok(())
+}
+
+fn catalog(language: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    CATALOGS
+        .iter()
+        .find(|&&(l, _)| l == language)
+        .map(|&(_, c)| c)
+}
+
+/// Language used when a server didn't choose one.
+fn fallback_language() -> String {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    settings
+        .get_str("language")
+        .ok()
+        .filter(|l| catalog(l).is_some())
+        .unwrap_or_else(|| REFERENCE_LANGUAGE.to_string())
+}
+
+/// Language of a server, or the fallback language outside of servers.
+pub fn language(guild_id: Option<GuildId>) -> String {
+    let languages = LANGUAGES
+        .read()
+        .expect("couldn't lock LANGUAGES for reading");
+    guild_id
+        .and_then(|g| languages.get(&g).cloned())
+        .unwrap_or_else(fallback_language)
+}
+
+/// Looks up a message in a language, falling back to the fallback language, then to the reference
+/// one. If the key isn't in any of them, the key itself is returned.
+pub fn get(language: &str, key: &str) -> String {
+    let lookup = |l: &str| {
+        catalog(l).and_then(|c| c.iter().find(|&&(k, _)| k == key).map(|&(_, m)| m))
+    };
+
+    lookup(language)
+        .or_else(|| lookup(&fallback_language()))
+        .or_else(|| lookup(REFERENCE_LANGUAGE))
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            warn!("missing message {} in language {}", key, language);
+            key.to_string()
+        })
+}
+
+/// Translates a message for a server.
+pub fn tr(guild_id: Option<GuildId>, key: &str) -> String {
+    get(&language(guild_id), key)
+}
+
+/// Translates a message for a server and fills its placeholders.
+pub fn tr_with(guild_id: Option<GuildId>, key: &str, placeholders: &[(&str, String)]) -> String {
+    fill_placeholders(&tr(guild_id, key), placeholders)
+}
+
+/// Keys of the reference catalog that are missing from a language, and keys of the language that
+/// don't exist in the reference catalog.
+pub fn catalog_differences(language: &str) -> (Vec<&'static str>, Vec<&'static str>) {
+    let reference = catalog(REFERENCE_LANGUAGE).unwrap_or(&[]);
+    let other = catalog(language).unwrap_or(&[]);
+    let has = |c: &[(&str, &str)], key: &str| c.iter().any(|&(k, _)| k == key);
+
+    (
+        reference
+            .iter()
+            .map(|&(k, _)| k)
+            .filter(|k| !has(other, k))
+            .collect(),
+        other
+            .iter()
+            .map(|&(k, _)| k)
+            .filter(|k| !has(reference, k))
+            .collect(),
+    )
+}
+
+fn set_language(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    let mut args = args;
+    // Unwrap is safe here because this command is only available in servers.
+    let server_id = msg.guild_id.unwrap();
+
+    if args.is_empty() {
+        let available = CATALOGS
+            .iter()
+            .map(|&(l, _)| l)
+            .collect::<Vec<&str>>()
+            .join(", ");
+        msg.channel_id.send_message(|m| {
+            m.content(tr_with(
+                Some(server_id),
+                "i18n.current",
+                &[("language", language(Some(server_id))), ("available", available)],
+            ))
+        })?;
+        return Ok(());
+    }
+
+    let new_language = args.single::<String>()?.to_lowercase();
+    if catalog(&new_language).is_none() {
+        return Err(tr_with(
+            Some(server_id),
+            "i18n.unknown",
+            &[("language", new_language)],
+        ).into());
+    }
+
+    {
+        let mut languages = LANGUAGES
+            .write()
+            .expect("couldn't lock LANGUAGES for writing");
+        languages.insert(server_id, new_language);
+    }
+    save_languages()?;
+
+    msg.channel_id
+        .send_message(|m| m.content(tr(Some(server_id), "i18n.set")))?;
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct I18n;
+
+impl Module for I18n {
+    fn name(&self) -> &'static str {
+        "i18n"
+    }
+
+    fn description(&self) -> &'static str {
+        "Language of the bot's messages"
+    }
+
+    fn register(&self, framework: StandardFramework) -> StandardFramework {
+        framework.group("Localization", |g| {
+            modules::group(
+                self.name(),
+                Permissions::MANAGE_GUILD,
+                g.desc("Language of the bot's messages")
+                    .command("language", |c| {
+                        c.desc("Shows or sets the language of the bot in this server.")
+                            .usage("[language]")
+                            .max_args(1)
+                            .guild_only(true)
+                            .exec(set_language)
+                    }),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// Literal keys given to `tr` and `tr_with` in a source text.
+    fn literal_keys(source: &str) -> Vec<String> {
+        let mut keys = Vec::new();
+        for name in &["tr(", "tr_with("] {
+            for (start, _) in source.match_indices(name) {
+                let in_a_name = source[..start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_alphanumeric() || c == '_');
+                if in_a_name {
+                    continue;
+                }
+                // Skip the server argument, up to the comma at the call's level.
+                let arguments = &source[start + name.len()..];
+                let mut depth = 0;
+                let mut key_start = None;
+                for (i, c) in arguments.char_indices() {
+                    match c {
+                        '(' | '[' => depth += 1,
+                        ')' | ']' if depth == 0 => break,
+                        ')' | ']' => depth -= 1,
+                        ',' if depth == 0 => {
+                            key_start = Some(i + 1);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                let key = key_start.map(|i| arguments[i..].trim_start()).unwrap_or("");
+                if let Some(key) = key.strip_prefix('"') {
+                    if let Some(end) = key.find('"') {
+                        keys.push(key[..end].to_string());
+                    }
+                }
+            }
+        }
+        keys
+    }
+
+    fn source_keys(directory: &Path, keys: &mut Vec<(String, String)>) {
+        for entry in fs::read_dir(directory).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                source_keys(&path, keys);
+            } else if path.extension().is_some_and(|e| e == "rs") {
+                let source = fs::read_to_string(&path).unwrap();
+                // Tests use made-up keys.
+                let code = source.split("#[cfg(test)]").next().unwrap_or("");
+                for key in literal_keys(code) {
+                    keys.push((path.display().to_string(), key));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn literal_keys_are_found() {
+        let source = r#"
+            tr(Some(server_id), "a.b");
+            i18n::tr_with(
+                guild_id(&msg),
+                "c.d",
+                &[("e", f(g, h))],
+            );
+            tr(None, key);
+            let s = str("x");
+        "#;
+        assert_eq!(literal_keys(source), vec!["a.b", "c.d"]);
+    }
+
+    #[test]
+    fn used_keys_are_in_the_catalogs() {
+        let mut keys = Vec::new();
+        source_keys(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut keys);
+        assert!(!keys.is_empty());
+
+        for &(language, messages) in CATALOGS {
+            let unknown: Vec<_> = keys
+                .iter()
+                .filter(|(_, key)| !messages.iter().any(|&(k, _)| k == key))
+                .collect();
+            assert!(
+                unknown.is_empty(),
+                "{} misses messages used in the code: {:?}",
+                language,
+                unknown
+            );
+        }
+    }
+
+    #[test]
+    fn catalogs_have_the_same_keys() {
+        for &(language, _) in CATALOGS {
+            let (missing, unknown) = catalog_differences(language);
+            assert!(
+                missing.is_empty(),
+                "{} misses messages: {:?}",
+                language,
+                missing
+            );
+            assert!(
+                unknown.is_empty(),
+                "{} has unknown messages: {:?}",
+                language,
+                unknown
+            );
+        }
+    }
+}
@@ -9,7 +9,9 @@ extern crate lazy_static;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate chrono;
 extern crate cron;
+extern crate ctrlc;
 extern crate serde_json;
 
 use serenity::framework::standard::CommandError;
@@ -20,15 +22,32 @@ use serenity::prelude::*;
 
 use std::collections::HashSet;
 use std::iter::FromIterator;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
+pub mod blocklist;
+pub mod confirmation;
+pub mod conversation;
+pub mod cooldowns;
+pub mod dispatch;
 pub mod dorothy;
+pub mod error;
+pub mod guild_log;
+pub mod i18n;
 pub mod misc;
+pub mod modules;
+pub mod paginator;
+pub mod permissions;
 pub mod premade_creator;
 pub mod utils;
 
-use dorothy::Module;
+use blocklist::Blocklist;
+use cooldowns::Cooldowns;
+use dorothy::{Dorothy, Registry};
+use guild_log::GuildLog;
+use i18n::I18n;
 use misc::Misc;
+use modules::Modules;
+use permissions::CommandPermissions;
 use premade_creator::PremadeCreator;
 
 lazy_static! {
@@ -60,32 +79,51 @@ fn main() {
         (token, HashSet::from_iter(owners), prefix)
     };
 
-    let dorothy = dorothy::Dorothy::default();
+    // Register modules here. Simply add them to the registry.
+    let registry = Arc::new(
+        Registry::default()
+            .with(PremadeCreator::default())
+            .with(Misc::default())
+            .with(I18n::default())
+            .with(GuildLog::default())
+            .with(Modules::default())
+            .with(CommandPermissions::default())
+            .with(Cooldowns::default())
+            .with(Blocklist::default()),
+    );
+
+    let dorothy = Dorothy::new(registry.clone());
 
     let mut client = serenity::client::Client::new(&token, dorothy).expect("couldn't login");
 
-    let framework = serenity::framework::StandardFramework::default();
-    let mut framework = framework
+    let framework = StandardFramework::default();
+    let framework = framework
         .configure(|c| c.prefix(&prefix).owners(owners))
-        .before(print_command_used)
+        .before(before_command)
         .after(command_error_logger)
         .on_dispatch_error(dispatch_error_handler)
-        .help(serenity::framework::standard::help_commands::with_embeds);
-
-    let mut modules: Vec<Box<Fn(StandardFramework) -> StandardFramework>> = Vec::new();
+        .help(modules::help);
 
-    // Register modules here. Simply put the result of their init function in the modules array.
+    client.with_framework(registry.register(framework));
 
-    modules.push(Box::new(&PremadeCreator::register));
-    modules.push(Box::new(&Misc::register));
+    // Stopping the shards makes `start` return, so that the modules are told on SIGINT and
+    // SIGTERM too.
+    let shard_manager = client.shard_manager.clone();
+    ctrlc::set_handler(move || {
+        info!("Stopping...");
+        shard_manager.lock().shutdown_all();
+    }).expect("couldn't set the signal handler");
 
-    for register in &mut modules {
-        framework = register(framework);
-    }
-
-    client.with_framework(framework);
+    let result = client.start();
+    registry.shutdown();
+    result.expect("couldn't start bot");
+}
 
-    client.start().expect("couldn't start bot");
+fn before_command(ctx: &mut Context, msg: &Message, cmd_name: &str) -> bool {
+    print_command_used(ctx, msg, cmd_name)
+        && check_blocklist(msg)
+        && check_permissions(msg, cmd_name)
+        && check_cooldown(msg, cmd_name)
 }
 
 fn print_command_used(_ctx: &mut Context, msg: &Message, cmd_name: &str) -> bool {
@@ -101,6 +139,36 @@ fn print_command_used(_ctx: &mut Context, msg: &Message, cmd_name: &str) -> bool
     true
 }
 
+// Blocked users and servers aren't answered: it'd only encourage them.
+fn check_blocklist(msg: &Message) -> bool {
+    let allowed = blocklist::check(msg);
+    if !allowed {
+        info!("Ignored, {} or server {:?} is blocked", msg.author.id, msg.guild_id);
+    }
+    allowed
+}
+
+fn check_permissions(msg: &Message, cmd_name: &str) -> bool {
+    match permissions::check(msg, cmd_name) {
+        Ok(()) => true,
+        Err(needed) => {
+            dispatch::handle_error(msg, DispatchError::LackOfPermissions(needed));
+            false
+        }
+    }
+}
+
+fn check_cooldown(msg: &Message, cmd_name: &str) -> bool {
+    match cooldowns::check(msg, cmd_name) {
+        Ok(()) => true,
+        Err(Some(seconds)) => {
+            dispatch::handle_error(msg, DispatchError::RateLimited(seconds));
+            false
+        }
+        Err(None) => false,
+    }
+}
+
 fn command_error_logger(
     _ctx: &mut Context,
     msg: &Message,
@@ -108,16 +176,12 @@ fn command_error_logger(
     result: Result<(), CommandError>,
 ) {
     if let Err(e) = result {
-        warn!("Command error for command {}: {:?}", cmd_name, e);
-        if let Err(e) = msg
-            .channel_id
-            .send_message(|m| m.content(format!("An error has occurred: `{:#?}`", e)))
-        {
-            warn!(
-                "An error has occurred while sending the error message (lol): {:?}",
-                e
-            );
-        }
+        error::report_command(
+            msg.guild_id,
+            msg.channel_id,
+            &format!("command {}", cmd_name),
+            e,
+        );
     }
 }
 
@@ -125,14 +189,5 @@ fn command_error_logger(
 // even if we don't control the signature of this function
 #[cfg_attr(feature = "cargo-clippy", allow(clippy_style))]
 fn dispatch_error_handler(_ctx: Context, msg: Message, err: DispatchError) {
-    warn!("An error occurred: {:?}", err);
-    if let Err(e) = msg
-        .channel_id
-        .send_message(|m| m.content(format!("An error has occurred: `{:#?}`", err)))
-    {
-        warn!(
-            "An error has occurred while sending the error message (lol): {:?}",
-            e
-        );
-    }
+    dispatch::handle_error(&msg, err);
 }
@@ -2,23 +2,39 @@
 //! Anybody can use these functions.
 use serenity::builder::*;
 use serenity::framework::standard::*;
+use serenity::model::permissions::Permissions;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
 use std::sync::Arc;
 
 use dorothy::Module;
+use i18n::tr;
+use modules;
+use paginator::send_paginated;
 use utils::*;
 
 #[derive(Default)]
 pub struct Misc;
 
 impl Module for Misc {
-    fn register(framework: StandardFramework) -> StandardFramework {
+    fn name(&self) -> &'static str {
+        "misc"
+    }
+
+    fn description(&self) -> &'static str {
+        "Miscellaneous commands"
+    }
+
+    fn register(&self, framework: StandardFramework) -> StandardFramework {
         framework.group("Misc", |g| {
-            g.desc("Miscellaneous commands")
-                .cmd("id", MentionIdsCommand::default())
-                .cmd("say", SayCommand::default())
+            modules::group(
+                self.name(),
+                Permissions::empty(),
+                g.desc("Miscellaneous commands")
+                    .cmd("id", MentionIdsCommand::default())
+                    .cmd("say", SayCommand::default()),
+            )
         })
     }
 }
@@ -64,76 +80,45 @@ impl Command for MentionIdsCommand {
 
     fn execute(&self, _ctx: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
         let mut args = args;
-        let mut embed = CreateEmbed::default();
         let subcommand = args.single::<String>()?;
 
-        match subcommand.as_str() {
+        let section = match subcommand.as_str() {
             "roles" => {
                 if msg.mention_roles.is_empty() {
-                    return Err("please mention roles when asking for role IDs!".into());
+                    return Err(tr(msg.guild_id, "misc.id.no_roles").into());
                 }
 
-                let roles = role_list_to_mentions(&msg.mention_roles);
-                let mut roles = if let Err(e) = roles {
-                    return Err(format!(
-                        "an error has occured while creating the role mentions: {}",
-                        e
-                    ).into());
-                } else {
-                    roles.unwrap()
-                };
-                embed = embed
-                    .field("Role IDs", roles.pop().unwrap(), false)
-                    .fields(roles.iter().map(|s| ("Role IDs (cont)", s, false)));
+                let roles = msg
+                    .mention_roles
+                    .iter()
+                    .map(|role| format!("{} -> {}", role.mention(), role.0))
+                    .collect();
+                Section::new(tr(msg.guild_id, "misc.id.roles"), roles)
             }
             "users" => {
                 if msg.mentions.is_empty() {
-                    return Err("please mention users when asking for user Ids!".into());
+                    return Err(tr(msg.guild_id, "misc.id.no_users").into());
                 }
 
-                let users = user_list_to_mentions(&msg.mentions);
-                let mut users = if let Err(e) = users {
-                    return Err(format!(
-                        "an error has occured while creating the user mentions: {}",
-                        e
-                    ).into());
-                } else {
-                    users.unwrap()
-                };
-                embed = embed
-                    .field("User IDs", users.pop().unwrap(), false)
-                    .fields(users.iter().map(|s| ("User IDs (cont)", s, false)));
+                let users = msg
+                    .mentions
+                    .iter()
+                    .map(|user| format!("{} -> {}", user.mention(), user.id.0))
+                    .collect();
+                Section::new(tr(msg.guild_id, "misc.id.users"), users)
             }
             _ => {
-                return Err("I don't understand.".into());
+                return Err(tr(msg.guild_id, "misc.id.unknown").into());
             }
-        }
+        };
 
-        msg.channel_id.send_message(|m| m.embed(|_| embed))?;
+        let embeds = paginate_embed(
+            &CreateEmbed::default(),
+            vec![section],
+            &tr(msg.guild_id, "cont"),
+        );
+        send_paginated(msg, embeds)?;
 
         Ok(())
     }
 }
-
-// Sweet, sweet code duplication....
-// @DRY
-
-fn user_list_to_mentions(users: &[User]) -> Result<Vec<String>, String> {
-    let mut users = users
-        .iter()
-        .map(|user| format!("{} -> {}", user.mention(), user.id.0));
-    let users = users
-        .try_fold(FoldStrlenState::new(900), &fold_by_strlen)?
-        .extract();
-    Ok(users.iter().map(|v| v.join("\n")).collect())
-}
-
-fn role_list_to_mentions(roles: &[RoleId]) -> Result<Vec<String>, String> {
-    let mut roles = roles
-        .iter()
-        .map(|role| format!("{} -> {}", role.mention(), role.0));
-    let roles = roles
-        .try_fold(FoldStrlenState::new(900), &fold_by_strlen)?
-        .extract();
-    Ok(roles.iter().map(|v| v.join("\n")).collect())
-}
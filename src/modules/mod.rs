@@ -0,0 +1,232 @@
+//! Modules enabled or disabled per server.
+//!
+//! Every module is enabled by default. A server's admins can disable one with `modules disable`:
+//! its commands are hidden from the help and ignored, and it doesn't post anything in the server
+//! on its own anymore. Disabled modules are stored in `data/modules.json`.
+
+use serenity::framework::standard::help_commands;
+use serenity::framework::standard::{Args, CommandError, CommandGroup, CreateGroup, HelpOptions};
+use serenity::framework::StandardFramework;
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use serde_json::{from_reader, to_writer_pretty};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::{Arc, RwLock};
+
+use dispatch;
+use dorothy::{self, Module};
+use error::Error;
+use i18n::{tr, tr_with};
+
+/// Name of this module.
+const NAME: &str = "modules";
+
+lazy_static! {
+    static ref DISABLED: RwLock<HashMap<GuildId, Vec<String>>> = {
+        RwLock::new(initialize_disabled())
+    };
+}
+
+fn initialize_disabled() -> HashMap<GuildId, Vec<String>> {
+    let file = File::open("data/modules.json");
+    if file.is_err() {
+        return HashMap::new();
+    }
+    let file = file.unwrap();
+    from_reader(file).unwrap_or_else(|e| {
+        warn!("couldn't deserialize disabled modules: {:?}", e);
+        HashMap::new()
+    })
+}
+
+fn save_disabled() -> Result<(), Error> {
+    let file = File::create("data/modules.json")?;
+
+    let disabled = DISABLED.read().expect("couldn't lock DISABLED for reading");
+    to_writer_pretty(file, &*disabled)?;
+    Ok(())
+}
+
+/// Whether a module is enabled in a server. Modules are always enabled in private messages.
+pub fn is_enabled(guild_id: Option<GuildId>, module: &str) -> bool {
+    let guild_id = match guild_id {
+        Some(guild_id) => guild_id,
+        None => return true,
+    };
+    let disabled = DISABLED.read().expect("couldn't lock DISABLED for reading");
+    disabled
+        .get(&guild_id)
+        .map(|modules| modules.iter().all(|m| m != module))
+        .unwrap_or(true)
+}
+
+/// Finishes the group of commands of a module: the commands only run where the module is
+/// enabled. `permissions` are needed by default to use them, servers can let other roles and
+/// users in with `perms allow`. Used on the finished group, when registering it.
+pub fn group(module: &'static str, permissions: Permissions, group: CreateGroup) -> CreateGroup {
+    dispatch::index(
+        module,
+        permissions,
+        group.check(move |_, msg, _, _| is_enabled(msg.guild_id, module)),
+    )
+}
+
+/// The help, without the groups of the modules disabled here.
+pub fn help(
+    ctx: &mut Context,
+    msg: &Message,
+    options: &HelpOptions,
+    groups: HashMap<String, Arc<CommandGroup>>,
+    args: &Args,
+) -> Result<(), CommandError> {
+    // The commands of a group all belong to the same module.
+    let groups = groups
+        .into_iter()
+        .filter(|(_, group)| {
+            group
+                .commands
+                .keys()
+                .next()
+                .and_then(|name| dispatch::module_of(name))
+                .map(|module| is_enabled(msg.guild_id, module))
+                .unwrap_or(true)
+        }).collect::<HashMap<String, Arc<CommandGroup>>>();
+
+    help_commands::with_embeds(ctx, msg, options, groups, args)
+}
+
+fn list(_: &mut Context, msg: &Message, _: Args) -> Result<(), CommandError> {
+    // Unwrap is safe here because this command is only available in servers.
+    let server_id = msg.guild_id.unwrap();
+
+    let lines = dorothy::loaded_modules()
+        .into_iter()
+        .map(|module| {
+            let state = if !module.can_be_disabled {
+                tr(Some(server_id), "modules.always_enabled")
+            } else if is_enabled(Some(server_id), module.name) {
+                tr(Some(server_id), "modules.enabled")
+            } else {
+                tr(Some(server_id), "modules.disabled")
+            };
+            format!("`{}` ({}): {}", module.name, state, module.description)
+        }).collect::<Vec<String>>();
+
+    msg.channel_id.send_message(|m| {
+        m.content(format!(
+            "{}\n{}",
+            tr(Some(server_id), "modules.list"),
+            lines.join("\n")
+        ))
+    })?;
+
+    Ok(())
+}
+
+/// Enables or disables the module named in the arguments.
+fn toggle(msg: &Message, args: Args, enable: bool) -> Result<(), CommandError> {
+    let mut args = args;
+    // Unwrap is safe here because this command is only available in servers.
+    let server_id = msg.guild_id.unwrap();
+
+    let name = args.single::<String>()?.to_lowercase();
+    let module = dorothy::loaded_modules()
+        .into_iter()
+        .find(|m| m.name == name)
+        .ok_or_else(|| {
+            Error::InvalidArgument(tr_with(
+                Some(server_id),
+                "modules.unknown",
+                &[("module", name.clone())],
+            ))
+        })?;
+    if !module.can_be_disabled {
+        return Err(Error::InvalidArgument(tr(Some(server_id), "modules.locked")).into());
+    }
+
+    {
+        let mut disabled = DISABLED.write().expect("couldn't lock DISABLED for writing");
+        let modules = disabled.entry(server_id).or_default();
+        modules.retain(|m| *m != name);
+        if !enable {
+            modules.push(name.clone());
+        }
+        if modules.is_empty() {
+            disabled.remove(&server_id);
+        }
+    }
+    save_disabled()?;
+    info!(
+        "Module {} {} in server {}",
+        name,
+        if enable { "enabled" } else { "disabled" },
+        server_id
+    );
+
+    let key = if enable {
+        "modules.enable.done"
+    } else {
+        "modules.disable.done"
+    };
+    msg.channel_id
+        .send_message(|m| m.content(tr_with(Some(server_id), key, &[("module", name)])))?;
+
+    Ok(())
+}
+
+fn enable(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    toggle(msg, args, true)
+}
+
+fn disable(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    toggle(msg, args, false)
+}
+
+#[derive(Default)]
+pub struct Modules;
+
+impl Module for Modules {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Modules enabled in the server"
+    }
+
+    // It couldn't be enabled back.
+    fn can_be_disabled(&self) -> bool {
+        false
+    }
+
+    fn register(&self, framework: StandardFramework) -> StandardFramework {
+        framework.group("Modules", |g| {
+            dispatch::index(
+                NAME,
+                Permissions::empty(),
+                g.desc("Modules enabled in the server")
+                    .required_permissions(Permissions::MANAGE_GUILD)
+                    .guild_only(true)
+                    .command("modules list", |c| {
+                        c.desc("Lists the modules and whether they're enabled in this server.")
+                            .max_args(0)
+                            .exec(list)
+                    }).command("modules enable", |c| {
+                        c.desc("Enables a module in this server.")
+                            .usage("<module>")
+                            .num_args(1)
+                            .exec(enable)
+                    }).command("modules disable", |c| {
+                        c.desc("Disables a module in this server: its commands are ignored and it doesn't post anything anymore.")
+                            .usage("<module>")
+                            .num_args(1)
+                            .exec(disable)
+                    }),
+            )
+        })
+    }
+}
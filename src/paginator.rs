@@ -0,0 +1,252 @@
+//! Interactive pagination of long outputs.
+//!
+//! `send_paginated` posts the first page of a list of embeds with ◀ and ▶ reactions. When the
+//! user who invoked the command clicks them, the message is edited to show the previous or next
+//! page. Other users' reactions are ignored.
+//! After `paginator.timeout` seconds (120 by default) without any click, the paginator expires:
+//! the bot removes its reactions and the message stays on the last page shown.
+//!
+//! The reactions are received by `Dorothy`, which forwards them to `handle_reaction`.
+
+use serenity::builder::CreateEmbed;
+use serenity::model::prelude::*;
+use serenity::Result as SerenityResult;
+
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use i18n::tr_with;
+use SETTINGS;
+
+const PREVIOUS: &str = "◀";
+const NEXT: &str = "▶";
+
+/// Timeout used if `paginator.timeout` isn't set.
+const DEFAULT_TIMEOUT: u64 = 120;
+
+lazy_static! {
+    static ref PAGINATORS: RwLock<HashMap<MessageId, Paginator>> = {
+        RwLock::new(HashMap::new())
+    };
+}
+
+/// A paginated message being browsed.
+struct Paginator {
+    channel_id: ChannelId,
+    author: UserId,
+    pages: Vec<CreateEmbed>,
+    current: usize,
+    expires: Instant,
+}
+
+fn timeout() -> Duration {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    Duration::from_secs(
+        settings
+            .get::<u64>("paginator.timeout")
+            .unwrap_or(DEFAULT_TIMEOUT),
+    )
+}
+
+/// Appends a page number to the footer of an embed, keeping its text if it has one.
+fn with_page_number(embed: CreateEmbed, page: String) -> CreateEmbed {
+    let text = embed
+        .0
+        .get(&"footer")
+        .and_then(|f| f.get("text"))
+        .and_then(Value::as_str)
+        .map(|t| format!("{} • {}", t, page))
+        .unwrap_or(page);
+    embed.footer(|f| f.text(text))
+}
+
+/// Sends pages in reply to a command. A single page is sent as is; several pages are browsable
+/// by the author of the command with reactions.
+pub fn send_paginated(msg: &Message, pages: Vec<CreateEmbed>) -> SerenityResult<Message> {
+    let count = pages.len();
+    let mut pages = pages.into_iter();
+    if count <= 1 {
+        let page = pages.next().unwrap_or_default();
+        return msg.channel_id.send_message(|m| m.embed(|_| page));
+    }
+
+    let pages = pages
+        .enumerate()
+        .map(|(i, page)| {
+            let number = tr_with(
+                msg.guild_id,
+                "paginator.page",
+                &[("page", (i + 1).to_string()), ("pages", count.to_string())],
+            );
+            with_page_number(page, number)
+        }).collect::<Vec<CreateEmbed>>();
+
+    let first = pages[0].clone();
+    let sent = msg.channel_id.send_message(|m| {
+        m.embed(|_| first)
+            .reactions(vec![ReactionType::from(PREVIOUS), ReactionType::from(NEXT)])
+    })?;
+
+    {
+        let mut paginators = PAGINATORS
+            .write()
+            .expect("couldn't lock PAGINATORS for writing");
+        paginators.insert(
+            sent.id,
+            Paginator {
+                channel_id: msg.channel_id,
+                author: msg.author.id,
+                pages,
+                current: 0,
+                expires: Instant::now() + timeout(),
+            },
+        );
+    }
+
+    let message_id = sent.id;
+    thread::spawn(move || expire(message_id));
+
+    Ok(sent)
+}
+
+/// Waits for a paginator to expire, then forgets it and removes the reactions. Every click pushes
+/// the expiration back.
+fn expire(message_id: MessageId) {
+    loop {
+        let expires = {
+            let paginators = PAGINATORS
+                .read()
+                .expect("couldn't lock PAGINATORS for reading");
+            match paginators.get(&message_id) {
+                Some(p) => p.expires,
+                None => return,
+            }
+        };
+
+        let now = Instant::now();
+        if expires <= now {
+            break;
+        }
+        thread::sleep(expires - now);
+    }
+
+    let paginator = PAGINATORS
+        .write()
+        .expect("couldn't lock PAGINATORS for writing")
+        .remove(&message_id);
+    if let Some(paginator) = paginator {
+        for emoji in &[PREVIOUS, NEXT] {
+            let result = paginator.channel_id.delete_reaction(
+                message_id,
+                None,
+                ReactionType::from(*emoji),
+            );
+            if let Err(e) = result {
+                debug!("couldn't remove reaction from paginator {}: {:?}", message_id, e);
+            }
+        }
+    }
+}
+
+/// Turns the page of a paginator if the reaction is a click from its author.
+pub fn handle_reaction(reaction: &Reaction) {
+    // Discord sometimes adds a variation selector to the arrows.
+    let step: isize = match reaction.emoji {
+        ReactionType::Unicode(ref name) => match name.trim_end_matches('\u{fe0f}') {
+            PREVIOUS => -1,
+            NEXT => 1,
+            _ => return,
+        },
+        _ => return,
+    };
+
+    let page = {
+        let mut paginators = PAGINATORS
+            .write()
+            .expect("couldn't lock PAGINATORS for writing");
+        let paginator = match paginators.get_mut(&reaction.message_id) {
+            Some(p) if p.author == reaction.user_id => p,
+            _ => return,
+        };
+
+        let count = paginator.pages.len() as isize;
+        paginator.current = ((paginator.current as isize + step + count) % count) as usize;
+        paginator.expires = Instant::now() + timeout();
+        paginator.pages[paginator.current].clone()
+    };
+
+    let result = reaction
+        .channel_id
+        .edit_message(reaction.message_id, |m| m.embed(|_| page));
+    if let Err(e) = result {
+        warn!("couldn't turn the page of paginator {}: {:?}", reaction.message_id, e);
+    }
+
+    // Remove the click so it can be clicked again. This needs the permission to manage messages;
+    // without it, the user has to remove their reaction themselves.
+    if let Err(e) = reaction.delete() {
+        debug!("couldn't remove reaction on paginator {}: {:?}", reaction.message_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use i18n;
+    use utils::{
+        embed_length, fill_placeholders, paginate_embed, Section, EMBED_FOOTER_LIMIT,
+        EMBED_TOTAL_LIMIT, PAGE_NUMBER_ROOM,
+    };
+
+    fn repeat(c: char, count: usize) -> String {
+        (0..count).map(|_| c).collect()
+    }
+
+    /// The longest page numbers of every language.
+    fn longest_page_numbers() -> Vec<String> {
+        ["en", "fr"]
+            .iter()
+            .map(|language| {
+                fill_placeholders(
+                    &i18n::get(language, "paginator.page"),
+                    &[("page", "999".to_string()), ("pages", "999".to_string())],
+                )
+            }).collect()
+    }
+
+    #[test]
+    fn page_numbers_fit_in_full_pages() {
+        // Six fields of 990 characters and a footer filling the rest of the page.
+        let footer = repeat('é', EMBED_TOTAL_LIMIT - PAGE_NUMBER_ROOM - 4 - 6 * 990);
+        let base = CreateEmbed::default().title("Jeux").footer(|f| f.text(footer));
+        let sections = (0..12)
+            .map(|i| Section::new((i % 10).to_string(), vec![repeat('é', 989)]))
+            .collect();
+        let pages = paginate_embed(&base, sections, "(cont)");
+        assert_eq!(pages.len(), 2);
+
+        for number in longest_page_numbers() {
+            for page in &pages {
+                assert_eq!(embed_length(page), EMBED_TOTAL_LIMIT - PAGE_NUMBER_ROOM);
+                let numbered = with_page_number(page.clone(), number.clone());
+                assert!(embed_length(&numbered) <= EMBED_TOTAL_LIMIT);
+            }
+        }
+    }
+
+    #[test]
+    fn page_numbers_fit_in_long_footers() {
+        let base = CreateEmbed::default().footer(|f| f.text(repeat('é', EMBED_FOOTER_LIMIT)));
+        let page = paginate_embed(&base, Vec::new(), "(cont)").remove(0);
+
+        for number in longest_page_numbers() {
+            let numbered = with_page_number(page.clone(), number);
+            let footer = numbered.0.get(&"footer").unwrap()["text"].as_str().unwrap();
+            assert!(footer.chars().count() <= EMBED_FOOTER_LIMIT);
+        }
+    }
+}
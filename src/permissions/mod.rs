@@ -0,0 +1,300 @@
+//! Who can use the commands of a server.
+//!
+//! Commands need permissions by default, like `MANAGE_GUILD` for the premade creator. Servers can
+//! let more roles and users use a command, or all the commands of a module, with `perms allow`:
+//! organizers can then run the polls without managing the server. Everyone with the default
+//! permissions keeps access. The rules are stored in `data/permissions.json`, and checked before
+//! every command.
+
+use serenity::framework::standard::{Args, CommandError};
+use serenity::framework::StandardFramework;
+use serenity::model::misc::Mentionable;
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use serde_json::{from_reader, to_writer_pretty};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::RwLock;
+
+use dispatch;
+use dorothy::{self, Module};
+use error::Error;
+use i18n::{tr, tr_with};
+use SETTINGS;
+
+/// Name of this module. Its commands, like the ones of the modules module, can't be given away:
+/// only server managers can change who can do what.
+const NAME: &str = "permissions";
+
+/// Modules whose commands can't be given to other roles and users.
+const LOCKED: &[&str] = &[NAME, "modules"];
+
+/// Roles and users allowed to use a command, or the commands of a module.
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct Rule {
+    roles: Vec<RoleId>,
+    users: Vec<UserId>,
+}
+
+impl Rule {
+    fn is_empty(&self) -> bool {
+        self.roles.is_empty() && self.users.is_empty()
+    }
+
+    fn allows(&self, user_id: UserId, roles: &[RoleId]) -> bool {
+        self.users.contains(&user_id) || self.roles.iter().any(|r| roles.contains(r))
+    }
+
+    fn describe(&self) -> String {
+        self.roles
+            .iter()
+            .map(RoleId::mention)
+            .chain(self.users.iter().map(UserId::mention))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+lazy_static! {
+    /// Rules of the servers, by command or module name.
+    static ref RULES: RwLock<HashMap<GuildId, HashMap<String, Rule>>> = {
+        RwLock::new(initialize_rules())
+    };
+}
+
+fn initialize_rules() -> HashMap<GuildId, HashMap<String, Rule>> {
+    let file = File::open("data/permissions.json");
+    if file.is_err() {
+        return HashMap::new();
+    }
+    let file = file.unwrap();
+    from_reader(file).unwrap_or_else(|e| {
+        warn!("couldn't deserialize permissions: {:?}", e);
+        HashMap::new()
+    })
+}
+
+fn save_rules() -> Result<(), Error> {
+    let file = File::create("data/permissions.json")?;
+
+    let rules = RULES.read().expect("couldn't lock RULES for reading");
+    to_writer_pretty(file, &*rules)?;
+    Ok(())
+}
+
+/// Whether a user is an owner of the bot.
+pub fn is_owner(user_id: UserId) -> bool {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    settings
+        .get_array("owners")
+        .unwrap_or_default()
+        .into_iter()
+        .any(|o| o.try_into::<u64>().ok() == Some(user_id.0))
+}
+
+/// Checks that the author of a message can use a command. If they can't, returns the permissions
+/// they'd need.
+pub fn check(msg: &Message, command: &str) -> Result<(), Permissions> {
+    let needed = match dispatch::default_permissions(command) {
+        Some(needed) if !needed.is_empty() => needed,
+        _ => return Ok(()),
+    };
+    if is_owner(msg.author.id) {
+        return Ok(());
+    }
+    let guild = match msg.guild() {
+        Some(guild) => guild,
+        None => return Err(needed),
+    };
+    let guild = guild.read();
+    if guild
+        .permissions_in(msg.channel_id, msg.author.id)
+        .contains(needed)
+    {
+        return Ok(());
+    }
+
+    let roles = guild
+        .members
+        .get(&msg.author.id)
+        .map(|m| m.roles.clone())
+        .unwrap_or_default();
+    let rules = RULES.read().expect("couldn't lock RULES for reading");
+    let allowed = rules
+        .get(&guild.id)
+        .map(|rules| {
+            let module = dispatch::module_of(command).unwrap_or_default();
+            [command, module]
+                .iter()
+                .filter_map(|target| rules.get(*target))
+                .any(|rule| rule.allows(msg.author.id, &roles))
+        }).unwrap_or(false);
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(needed)
+    }
+}
+
+/// Reads the target of a rule: a command (quoted if it has several words) or a module.
+fn target(server_id: GuildId, args: &mut Args) -> Result<String, Error> {
+    let target = args
+        .single_quoted::<String>()
+        .map_err(|_| Error::InvalidArgument(tr(Some(server_id), "perms.no_target")))?;
+    let module = match dispatch::module_of(&target) {
+        Some(module) => module,
+        None => dorothy::loaded_modules()
+            .into_iter()
+            .map(|module| module.name)
+            .find(|&name| name == target)
+            .ok_or_else(|| {
+                Error::InvalidArgument(tr_with(
+                    Some(server_id),
+                    "perms.unknown",
+                    &[("target", target.clone())],
+                ))
+            })?,
+    };
+    if LOCKED.contains(&module) {
+        return Err(Error::InvalidArgument(tr(Some(server_id), "perms.locked")));
+    }
+
+    Ok(target)
+}
+
+fn list(_: &mut Context, msg: &Message, _: Args) -> Result<(), CommandError> {
+    // Unwrap is safe here because this command is only available in servers.
+    let server_id = msg.guild_id.unwrap();
+
+    let lines = {
+        let rules = RULES.read().expect("couldn't lock RULES for reading");
+        let mut lines = rules
+            .get(&server_id)
+            .map(|rules| {
+                rules
+                    .iter()
+                    .map(|(target, rule)| format!("`{}`: {}", target, rule.describe()))
+                    .collect::<Vec<String>>()
+            }).unwrap_or_default();
+        lines.sort();
+        lines
+    };
+
+    let content = if lines.is_empty() {
+        tr(Some(server_id), "perms.none")
+    } else {
+        format!("{}\n{}", tr(Some(server_id), "perms.list"), lines.join("\n"))
+    };
+    // Without mentions, so that nobody gets pinged.
+    msg.channel_id.send_message(|m| m.embed(|e| e.description(content)))?;
+
+    Ok(())
+}
+
+/// Adds or removes the mentioned roles and users of the rule of the target in the arguments.
+fn change(msg: &Message, args: Args, allow: bool) -> Result<(), CommandError> {
+    let mut args = args;
+    // Unwrap is safe here because this command is only available in servers.
+    let server_id = msg.guild_id.unwrap();
+
+    let target = target(server_id, &mut args)?;
+    let users = msg.mentions.iter().map(|u| u.id).collect::<Vec<UserId>>();
+    if msg.mention_roles.is_empty() && users.is_empty() {
+        return Err(Error::InvalidArgument(tr(Some(server_id), "perms.no_mentions")).into());
+    }
+
+    let described = {
+        let mut rules = RULES.write().expect("couldn't lock RULES for writing");
+        let server_rules = rules.entry(server_id).or_default();
+        let described = {
+            let rule = server_rules.entry(target.clone()).or_default();
+            rule.roles.retain(|r| !msg.mention_roles.contains(r));
+            rule.users.retain(|u| !users.contains(u));
+            if allow {
+                rule.roles.extend(msg.mention_roles.iter().cloned());
+                rule.users.extend(users);
+            }
+            rule.describe()
+        };
+        server_rules.retain(|_, rule| !rule.is_empty());
+        if server_rules.is_empty() {
+            rules.remove(&server_id);
+        }
+        described
+    };
+    save_rules()?;
+    info!(
+        "Permissions of {} in server {} changed by {}: {}",
+        target, server_id, msg.author.id, described
+    );
+
+    let content = if described.is_empty() {
+        tr_with(Some(server_id), "perms.default", &[("target", target)])
+    } else {
+        tr_with(
+            Some(server_id),
+            "perms.changed",
+            &[("target", target), ("allowed", described)],
+        )
+    };
+    msg.channel_id
+        .send_message(|m| m.embed(|e| e.description(content)))?;
+
+    Ok(())
+}
+
+fn allow(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    change(msg, args, true)
+}
+
+fn revoke(_: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+    change(msg, args, false)
+}
+
+#[derive(Default)]
+pub struct CommandPermissions;
+
+impl Module for CommandPermissions {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Who can use the commands in the server"
+    }
+
+    fn can_be_disabled(&self) -> bool {
+        false
+    }
+
+    fn register(&self, framework: StandardFramework) -> StandardFramework {
+        framework.group("Permissions", |g| {
+            dispatch::index(
+                NAME,
+                Permissions::empty(),
+                g.desc("Who can use the commands in the server")
+                    .required_permissions(Permissions::MANAGE_GUILD)
+                    .guild_only(true)
+                    .command("perms list", |c| {
+                        c.desc("Lists the roles and users allowed to use commands on top of the defaults.")
+                            .max_args(0)
+                            .exec(list)
+                    }).command("perms allow", |c| {
+                        c.desc("Lets roles and users use a command, or all the commands of a module.")
+                            .usage("<command|module> <@role|@user...>")
+                            .min_args(2)
+                            .exec(allow)
+                    }).command("perms revoke", |c| {
+                        c.desc("Takes a command, or the commands of a module, back from roles and users.")
+                            .usage("<command|module> <@role|@user...>")
+                            .min_args(2)
+                            .exec(revoke)
+                    }),
+            )
+        })
+    }
+}
@@ -0,0 +1,400 @@
+//! Health check of the live configurations against their servers.
+//!
+//! Channels, roles and custom emojis get deleted, and permissions change, without the
+//! configuration knowing. `pmconfig check` lists what's wrong with the configuration of a server,
+//! and every server is checked daily (at `premade-creator.check`, a cron expression, noon UTC by
+//! default): the owner of a server with problems gets them in a private message.
+//!
+//! The permissions are also checked when the bot starts and when a server becomes available, so
+//! that they aren't only found missing when a poll fails. What's missing is logged, and kept for
+//! the owners of the bot to see with `status`.
+
+use serenity::builder::*;
+use serenity::framework::standard::*;
+use serenity::model::misc::Mentionable;
+use serenity::model::permissions::Permissions;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::*;
+use serenity::CACHE;
+
+use chrono::{DateTime, Utc};
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use error::Error;
+use guild_log::{self, Level};
+use i18n::{tr, tr_with};
+use modules;
+use paginator::send_paginated;
+use utils::*;
+
+use super::{Server, CONFIG};
+
+/// Schedule of the daily check if `premade-creator.check` isn't set.
+pub const DEFAULT_SCHEDULE: &str = "0 0 12 * * *";
+
+/// Permissions needed in the channel of the polls.
+fn poll_permissions() -> Permissions {
+    Permissions::READ_MESSAGES
+        | Permissions::SEND_MESSAGES
+        | Permissions::EMBED_LINKS
+        | Permissions::ADD_REACTIONS
+        | Permissions::READ_MESSAGE_HISTORY
+}
+
+/// Permissions needed in the channels of the games.
+fn game_permissions() -> Permissions {
+    Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS
+}
+
+/// Permissions needed in each channel of a configuration. Pinging roles (or everyone) needs
+/// `MENTION_EVERYONE`.
+fn needed_permissions(server: &Server) -> Vec<(ChannelId, Permissions)> {
+    let mention = |roles: &Option<Vec<RoleId>>| match roles {
+        Some(_) => Permissions::MENTION_EVERYONE,
+        None => Permissions::empty(),
+    };
+
+    let mut needed = vec![(
+        server.channel_id,
+        poll_permissions() | mention(&server.role_ids),
+    )];
+    for game in &server.games {
+        let permissions = game_permissions() | mention(&game.role_ids);
+        match needed.iter_mut().find(|(c, _)| *c == game.channel_id) {
+            Some((_, p)) => *p |= permissions,
+            None => needed.push((game.channel_id, permissions)),
+        }
+    }
+    needed
+}
+
+/// Permissions missing in the existing channels of a configuration.
+fn missing_permissions(guild: &Guild, server: &Server) -> Vec<(ChannelId, Permissions)> {
+    let bot_id = CACHE.read().user.id;
+    needed_permissions(server)
+        .into_iter()
+        .filter(|(channel_id, _)| guild.channels.contains_key(channel_id))
+        .map(|(channel_id, needed)| {
+            (channel_id, needed - guild.permissions_in(channel_id, bot_id))
+        }).filter(|(_, missing)| !missing.is_empty())
+        .collect()
+}
+
+/// Result of the last permission check of a server.
+struct Status {
+    checked: DateTime<Utc>,
+    missing: Vec<(ChannelId, Permissions)>,
+}
+
+lazy_static! {
+    static ref STATUS: RwLock<HashMap<GuildId, Status>> = RwLock::new(HashMap::new());
+}
+
+/// Lists the problems of a server's configuration.
+pub fn problems(server_id: GuildId, server: &Server) -> Vec<String> {
+    let guild = match server_id.to_guild_cached() {
+        Some(guild) => guild,
+        None => return vec![tr(Some(server_id), "check.no_guild")],
+    };
+    let guild = guild.read();
+
+    let mut problems = Vec::new();
+    for (channel_id, _) in needed_permissions(server) {
+        if !guild.channels.contains_key(&channel_id) {
+            problems.push(tr_with(
+                Some(server_id),
+                "check.channel_missing",
+                &[("channel", format!("{} ({})", channel_id.mention(), channel_id))],
+            ));
+        }
+    }
+    for (channel_id, missing) in missing_permissions(&guild, server) {
+        problems.push(tr_with(
+            Some(server_id),
+            "check.permissions",
+            &[
+                ("channel", format!("{} ({})", channel_id.mention(), channel_id)),
+                ("permissions", permission_names(missing)),
+            ],
+        ));
+    }
+
+    let roles = Some(&server.role_ids)
+        .into_iter()
+        .chain(server.games.iter().map(|g| &g.role_ids))
+        .flat_map(|roles| roles.iter().flat_map(|r| r.iter()));
+    for role_id in roles {
+        if !guild.roles.contains_key(role_id) {
+            problems.push(tr_with(
+                Some(server_id),
+                "check.role_missing",
+                &[("role", role_id.to_string())],
+            ));
+        }
+    }
+
+    for game in &server.games {
+        if let ReactionType::Custom { id, .. } = game.emoji {
+            if !guild.emojis.contains_key(&id) {
+                problems.push(tr_with(
+                    Some(server_id),
+                    "check.emoji_missing",
+                    &[("game", game.name.clone())],
+                ));
+            }
+        }
+    }
+
+    // Channels and roles can be used several times.
+    let mut unique = Vec::new();
+    for problem in problems {
+        if !unique.contains(&problem) {
+            unique.push(problem);
+        }
+    }
+    unique
+}
+
+/// Problems as embeds.
+fn problem_embeds(server_id: GuildId, problems: Vec<String>) -> Vec<CreateEmbed> {
+    let base = CreateEmbed::default()
+        .title(tr(Some(server_id), "check.title"))
+        .color(Colour::RED);
+    paginate_embed(
+        &base,
+        vec![Section::new(tr(Some(server_id), "check.problems"), problems)],
+        &tr(Some(server_id), "cont"),
+    )
+}
+
+/// Sends embeds about a server to its owner, in a private message.
+pub fn tell_owner(server_id: GuildId, embeds: Vec<CreateEmbed>) {
+    let owner_id = match server_id.to_guild_cached() {
+        Some(guild) => guild.read().owner_id,
+        None => {
+            warn!("Couldn't find the owner of server {}", server_id);
+            return;
+        }
+    };
+    let result = owner_id
+        .create_dm_channel()
+        .and_then(|dm| send_embeds(dm.id, embeds));
+    if let Err(e) = result {
+        warn!(
+            "Couldn't send a message to the owner of server {}: {:?}",
+            server_id, e
+        );
+    }
+}
+
+/// Checks every server, and tells the owners of the ones with problems.
+pub fn check_all() {
+    // Found from the cache, the config is unlocked before sending anything.
+    let found = {
+        let config = CONFIG.read().expect("couldn't lock config for reading");
+        config
+            .iter()
+            .filter(|(server_id, _)| modules::is_enabled(Some(**server_id), super::NAME))
+            .map(|(server_id, server)| (*server_id, problems(*server_id, server)))
+            .filter(|(_, problems)| !problems.is_empty())
+            .collect::<Vec<(GuildId, Vec<String>)>>()
+    };
+
+    for (server_id, problems) in found {
+        info!(
+            "{} problems found in the config of server {}",
+            problems.len(),
+            server_id
+        );
+        guild_log::post(
+            server_id,
+            Level::Warning,
+            format!(
+                "{}\n{}",
+                tr(Some(server_id), "check.title"),
+                problems.join("\n")
+            ),
+        );
+        tell_owner(server_id, problem_embeds(server_id, problems));
+    }
+}
+
+/// Checks the permissions of the bot in the channels of a server's configuration, logs what's
+/// missing and keeps it for `status`. Servers that aren't in the cache yet are left for later.
+pub fn self_check(server_id: GuildId) {
+    let guild = match server_id.to_guild_cached() {
+        Some(guild) => guild,
+        None => return,
+    };
+    let missing = {
+        let config = CONFIG.read().expect("couldn't lock CONFIG for reading");
+        match config.get(&server_id) {
+            Some(server) => missing_permissions(&guild.read(), server),
+            None => return,
+        }
+    };
+
+    if missing.is_empty() {
+        info!("permission check: server={} ok", server_id);
+    }
+    for (channel_id, permissions) in &missing {
+        warn!(
+            "permission check: server={} channel={} missing={:?}",
+            server_id, channel_id, permissions
+        );
+        guild_log::post(
+            server_id,
+            Level::Warning,
+            tr_with(
+                Some(server_id),
+                "check.permissions",
+                &[
+                    ("channel", format!("{} ({})", channel_id.mention(), channel_id)),
+                    ("permissions", format!("{:?}", permissions)),
+                ],
+            ),
+        );
+    }
+
+    STATUS
+        .write()
+        .expect("couldn't lock STATUS for writing")
+        .insert(
+            server_id,
+            Status {
+                checked: Utc::now(),
+                missing,
+            },
+        );
+}
+
+/// Checks the permissions in every configured server.
+pub fn self_check_all() {
+    let server_ids = {
+        let config = CONFIG.read().expect("couldn't lock CONFIG for reading");
+        config.keys().cloned().collect::<Vec<GuildId>>()
+    };
+    for server_id in server_ids {
+        self_check(server_id);
+    }
+}
+
+/// Forgets the permission check of a server the bot isn't in anymore.
+pub fn forget_status(server_id: GuildId) {
+    STATUS
+        .write()
+        .expect("couldn't lock STATUS for writing")
+        .remove(&server_id);
+}
+
+#[derive(Default)]
+pub struct CheckCommand;
+
+/// Checks the live configuration of the server.
+impl Command for CheckCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some("Checks that the channels, roles and emojis of the configuration still exist, and that I have the permissions I need.".to_string());
+        options.help_available = true;
+        options.max_args = Some(0);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+
+        let problems = {
+            let config = CONFIG.read().expect("couldn't lock CONFIG for reading");
+            let server = config.get(&server_id).ok_or(Error::MissingConfig)?;
+            problems(server_id, server)
+        };
+
+        if problems.is_empty() {
+            msg.channel_id
+                .send_message(|m| m.content(tr(Some(server_id), "check.ok")))?;
+        } else {
+            send_paginated(msg, problem_embeds(server_id, problems))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct StatusCommand;
+
+/// Shows the last permission check of every configured server.
+impl Command for StatusCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some(
+            "Shows the permissions I'm missing in the configured servers, as of their last check."
+                .to_string(),
+        );
+        options.help_available = true;
+        options.owners_only = true;
+        options.max_args = Some(0);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
+        let sections = {
+            let status = STATUS.read().expect("couldn't lock STATUS for reading");
+            let mut servers = status.iter().collect::<Vec<(&GuildId, &Status)>>();
+            // Servers with problems first.
+            servers.sort_by_key(|(id, s)| (s.missing.is_empty(), **id));
+            servers
+                .into_iter()
+                .map(|(server_id, status)| {
+                    let name = server_id
+                        .to_guild_cached()
+                        .map(|g| g.read().name.clone())
+                        .unwrap_or_else(|| server_id.to_string());
+                    let mut lines = vec![tr_with(
+                        msg.guild_id,
+                        "check.status.checked",
+                        &[(
+                            "time",
+                            status.checked.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                        )],
+                    )];
+                    if status.missing.is_empty() {
+                        lines.push(tr(msg.guild_id, "check.status.ok"));
+                    }
+                    for (channel_id, missing) in &status.missing {
+                        lines.push(tr_with(
+                            msg.guild_id,
+                            "check.permissions",
+                            &[
+                                ("channel", format!("{} ({})", channel_id.mention(), channel_id)),
+                                ("permissions", permission_names(*missing)),
+                            ],
+                        ));
+                    }
+                    Section::new(format!("{} ({})", name, server_id), lines)
+                }).collect::<Vec<Section>>()
+        };
+
+        if sections.is_empty() {
+            msg.channel_id
+                .send_message(|m| m.content(tr(msg.guild_id, "check.status.none")))?;
+            return Ok(());
+        }
+
+        let base = CreateEmbed::default()
+            .title(tr(msg.guild_id, "check.status.title"))
+            .color(Colour::BLUE);
+        send_paginated(
+            msg,
+            paginate_embed(&base, sections, &tr(msg.guild_id, "cont")),
+        )?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use cron;
+use chrono::prelude::*;
 
 use serenity::builder::*;
 use serenity::framework::standard::*;
@@ -7,12 +7,26 @@ use serenity::model::prelude::*;
 use serenity::prelude::*;
 use serenity::utils::*;
 
+use serde_json::{self, Value};
+
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::RwLock;
-
+use std::sync::{RwLock, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+
+use confirmation;
+use error::{self, Error};
+use i18n::{tr, tr_with};
+use paginator::send_paginated;
+use utils::*;
+
+use super::diff;
+use super::history;
+use super::schedule;
+use super::skip;
 use super::Server;
 use super::CONFIG;
+use SETTINGS;
 
 #[derive(Default)]
 pub struct GetCommand;
@@ -26,14 +40,99 @@ pub struct AddRolesCommand;
 pub struct AddGameCommand;
 #[derive(Default)]
 pub struct CommitCommand;
+#[derive(Default)]
+pub struct TemplateCommand;
+#[derive(Default)]
+pub struct DiscardCommand;
+#[derive(Default)]
+pub struct DiffCommand;
+#[derive(Default)]
+pub struct ExportCommand;
+#[derive(Default)]
+pub struct ImportCommand;
+
+/// Drafts are per admin: (server, admin editing it).
+pub type DraftKey = (GuildId, UserId);
+
+/// Biggest configuration file accepted by the import command, in bytes.
+const MAX_IMPORT_SIZE: u64 = 1024 * 1024;
+
+/// Lifetime of a draft if `premade-creator.draft_lifetime` isn't set.
+const DEFAULT_DRAFT_LIFETIME: u64 = 3600;
 
 lazy_static! {
-    static ref INCOMPLETE_SERVERS: RwLock<HashMap<GuildId, Server>> = {
+    static ref INCOMPLETE_SERVERS: RwLock<HashMap<DraftKey, Draft>> = {
         // @TUNE Change the number of reserved slots
         RwLock::new(HashMap::with_capacity(500))
     };
 }
 
+/// A configuration being edited, and when it was last edited.
+pub struct Draft {
+    server: Server,
+    last_edit: Instant,
+}
+
+impl Draft {
+    pub fn new(server: Server) -> Self {
+        Draft {
+            server,
+            last_edit: Instant::now(),
+        }
+    }
+}
+
+/// Drafts that haven't been edited for `premade-creator.draft_lifetime` seconds are thrown away.
+fn draft_lifetime() -> Duration {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    Duration::from_secs(
+        settings
+            .get::<u64>("premade-creator.draft_lifetime")
+            .unwrap_or(DEFAULT_DRAFT_LIFETIME),
+    )
+}
+
+/// Locks the drafts for writing, throwing the stale ones away.
+pub fn write_drafts() -> RwLockWriteGuard<'static, HashMap<DraftKey, Draft>> {
+    let mut drafts = INCOMPLETE_SERVERS
+        .write()
+        .expect("couldn't lock INCOMPLETE_SERVERS for writing");
+    let lifetime = draft_lifetime();
+    drafts.retain(|_, d| d.last_edit.elapsed() < lifetime);
+    drafts
+}
+
+/// The draft of an admin, empty if they didn't start one. Getting it counts as an edit.
+fn draft_mut(drafts: &mut HashMap<DraftKey, Draft>, key: DraftKey) -> &mut Server {
+    let draft = drafts
+        .entry(key)
+        .or_insert_with(|| Draft::new(Server::default()));
+    draft.last_edit = Instant::now();
+    &mut draft.server
+}
+
+/// Warns that other admins are editing the configuration of the server too.
+pub fn other_editors(key: DraftKey) -> Option<String> {
+    let (server_id, user_id) = key;
+    let drafts = INCOMPLETE_SERVERS
+        .read()
+        .expect("couldn't lock INCOMPLETE_SERVERS for reading");
+    let others = drafts
+        .keys()
+        .filter(|&&(s, u)| s == server_id && u != user_id)
+        .map(|&(_, u)| u.mention())
+        .collect::<Vec<String>>();
+    if others.is_empty() {
+        return None;
+    }
+
+    Some(tr_with(
+        Some(server_id),
+        "pm.draft.others",
+        &[("users", others.join(", "))],
+    ))
+}
+
 // @DRY The code here has a lot of redundancies. Better clean up someday.
 
 /// Gets a server from the loaded config or a default value, then prints the data in an embed.
@@ -56,27 +155,32 @@ impl Command for GetCommand {
         let server = config.get(&server_id);
 
         let server = match server {
-            // Server config exists, put it in the INCOMPLETE_SERVERS map.
+            // Server config exists, put it in the admin's draft.
             Some(s) => s.clone(),
-            None => {
-                return Err(CommandError(
-                    "Couldn't find server in config file".to_string(),
-                ))
-            }
+            None => return Err(Error::MissingConfig.into()),
         };
 
-        msg.channel_id.send_message(|m| {
-            m.embed(|_| {
-                display_server(&server)
-                    .title("Server configuration loaded!")
-                    .color(Colour::from_rgb(120, 17, 176))
-            })
-        })?;
-
-        let mut incomplete_servers = INCOMPLETE_SERVERS
-            .write()
-            .expect("couldn't lock INCOMPLETE_SERVERS for writing");
-        incomplete_servers.insert(server_id, server);
+        let mut sections = server_sections(server_id, &server);
+        sections.push(Section::new(
+            tr(Some(server_id), "pm.display.skipped"),
+            skip::describe_skips(server_id),
+        ));
+        let mut base = CreateEmbed::default()
+            .title(tr(Some(server_id), "pm.get.title"))
+            .color(Colour(server.templates.colour()));
+        if let Some(warning) = split_warning(server_id, &server) {
+            base = base.description(warning);
+        }
+        send_paginated(
+            msg,
+            paginate_embed(&base, sections, &tr(Some(server_id), "cont")),
+        )?;
+
+        let key = (server_id, msg.author.id);
+        write_drafts().insert(key, Draft::new(server));
+        if let Some(warning) = other_editors(key) {
+            msg.channel_id.send_message(|m| m.content(warning))?;
+        }
 
         Ok(())
     }
@@ -86,8 +190,8 @@ impl Command for GetCommand {
 impl Command for CreateCommand {
     fn options(&self) -> Arc<CommandOptions> {
         let mut options = CommandOptions::default();
-        options.desc = Some("Creates a new configuration. the start and end expressions are cron syntax, but with an additionnal field on the left for seconds.".to_string());
-        options.usage = Some("<channel_id> <start exp> <end exp>".to_string());
+        options.desc = Some("Creates a new configuration. The start and end are either plain schedules like \"weekdays at 19:00\" or \"sat,sun at 14:30\" (UTC), or cron syntax with an additionnal field on the left for seconds. The end can also be relative to the start, like +3h.".to_string());
+        options.usage = Some("<channel_id> <\"start\"> <\"end\">".to_string());
         options.help_available = true;
         options.max_args = Some(3);
         options.min_args = Some(3);
@@ -102,9 +206,7 @@ impl Command for CreateCommand {
         let mut server = Server::default();
 
         if args.remaining() < 3 {
-            return Err(CommandError(
-                "The Create command takes 3 arguments".to_string(),
-            ));
+            return Err(CommandError(tr(Some(server_id), "pm.create.args")));
         }
 
         server.channel_id = ChannelId(args.single()?);
@@ -114,37 +216,42 @@ impl Command for CreateCommand {
         // Get the two strings that we will use in the server
         let start: String = args.single_quoted()?;
         let end: String = args.single_quoted()?;
-        // Check if they are valid syntax
-        let _t: cron::Schedule = start.parse()?;
-        let _t: cron::Schedule = end.parse()?;
+        // Check if they are valid syntax, and turn plain schedules into cron expressions
+        let (start, end) = schedule::parse_events(server_id, &start, &end)?;
+
+        let polls = schedule::check_events(server_id, &start, &end)?;
 
         // If we're here then everything is valid.
         server.start = start;
         server.end = end;
 
-        msg.channel_id.send_message(|m| {
-            m.embed(|_| {
-                display_server(&server)
-                    .title("New configuration created!")
-                    .color(Colour::from_rgb(120, 17, 176))
-            })
-        })?;
-
-        let mut incomplete_servers = INCOMPLETE_SERVERS
-            .write()
-            .expect("couldn't lock INCOMPLETE_SERVERS for writing");
-        incomplete_servers.insert(server_id, server);
+        let mut sections = server_sections(server_id, &server);
+        sections.push(next_polls_section(server_id, &polls));
+        send_embeds(
+            msg.channel_id,
+            paginate_embed(
+                &server_embed(server_id, &server, tr(Some(server_id), "pm.create.title")),
+                sections,
+                &tr(Some(server_id), "cont"),
+            ),
+        )?;
+
+        let key = (server_id, msg.author.id);
+        write_drafts().insert(key, Draft::new(server));
+        if let Some(warning) = other_editors(key) {
+            msg.channel_id.send_message(|m| m.content(warning))?;
+        }
 
         Ok(())
     }
 }
 
-/// Sets the values of the server if it's loaded in the INCOMPLETE_SERVERS list
+/// Sets the values of the server in the admin's draft
 impl Command for SetCommand {
     fn options(&self) -> Arc<CommandOptions> {
         let mut options = CommandOptions::default();
-        options.desc = Some("Sets the values of the server if it's currently being edited (eg, after create or get). Start and end expressions are cron syntax but with an additionnal field on the left for seconds.".to_string());
-        options.usage = Some("<channel_id> <start exp> <end exp>".to_string());
+        options.desc = Some("Sets the values of the server if it's currently being edited (eg, after create or get). The start and end are either plain schedules like \"weekdays at 19:00\" (UTC), or cron syntax with an additionnal field on the left for seconds. The end can also be relative to the start, like +3h.".to_string());
+        options.usage = Some("<channel_id> <\"start\"> <\"end\">".to_string());
         options.help_available = true;
         options.max_args = Some(3);
         options.min_args = Some(3);
@@ -159,9 +266,7 @@ impl Command for SetCommand {
         let server_id = msg.guild_id.unwrap();
 
         if args.remaining() < 3 {
-            return Err(CommandError(
-                "The Create command takes 3 arguments".to_string(),
-            ));
+            return Err(CommandError(tr(Some(server_id), "pm.create.args")));
         }
 
         let channel_id = ChannelId(args.single()?);
@@ -171,16 +276,14 @@ impl Command for SetCommand {
         // Get the two strings that we will use in the server
         let start: String = args.single_quoted()?;
         let end: String = args.single_quoted()?;
-        // Check if they are valid syntax
-        let _t: cron::Schedule = start.parse()?;
-        let _t: cron::Schedule = end.parse()?;
+        // Check if they are valid syntax, and turn plain schedules into cron expressions
+        let (start, end) = schedule::parse_events(server_id, &start, &end)?;
+        let polls = schedule::check_events(server_id, &start, &end)?;
 
         // If we're here then everything is valid.
         {
-            let mut incomplete_servers = INCOMPLETE_SERVERS
-                .write()
-                .expect("couldn't lock INCOMPLETE_SERVERS for writing");
-            let server = incomplete_servers.entry(server_id).or_default();
+            let mut incomplete_servers = write_drafts();
+            let server = draft_mut(&mut incomplete_servers, (server_id, msg.author.id));
             server.channel_id = channel_id;
             server.start = start;
             server.end = end;
@@ -191,15 +294,21 @@ impl Command for SetCommand {
         let incomplete_servers = INCOMPLETE_SERVERS
             .read()
             .expect("couldn't lock INCOMPLETE_SERVERS for reading");
-        // Once we get here there *is* a server in the map so it's safe to unwrap.
-        let server = incomplete_servers.get(&server_id).unwrap();
-        msg.channel_id.send_message(|m| {
-            m.embed(|_| {
-                display_server(&server)
-                    .title("Server configuration modified (don't forget to commit it)")
-                    .color(Colour::from_rgb(120, 17, 176))
-            })
-        })?;
+        // Once we get here there *is* a draft in the map so it's safe to unwrap.
+        let server = &incomplete_servers
+            .get(&(server_id, msg.author.id))
+            .unwrap()
+            .server;
+        let mut sections = server_sections(server_id, server);
+        sections.push(next_polls_section(server_id, &polls));
+        send_embeds(
+            msg.channel_id,
+            paginate_embed(
+                &server_embed(server_id, server, tr(Some(server_id), "pm.set.title")),
+                sections,
+                &tr(Some(server_id), "cont"),
+            ),
+        )?;
 
         Ok(())
     }
@@ -230,10 +339,8 @@ impl Command for AddRolesCommand {
             .collect();
 
         {
-            let mut incomplete_servers = INCOMPLETE_SERVERS
-                .write()
-                .expect("couldn't lock INCOMPLETE_SERVERS for writing");
-            let server = incomplete_servers.entry(server_id).or_default();
+            let mut incomplete_servers = write_drafts();
+            let server = draft_mut(&mut incomplete_servers, (server_id, msg.author.id));
             match server.role_ids {
                 None => server.role_ids = Some(roles),
                 Some(ref mut s) => s.append(&mut roles),
@@ -245,15 +352,15 @@ impl Command for AddRolesCommand {
         let incomplete_servers = INCOMPLETE_SERVERS
             .read()
             .expect("couldn't lock INCOMPLETE_SERVERS for reading");
-        // Once we get here there *is* a server in the map so it's safe to unwrap.
-        let server = incomplete_servers.get(&server_id).unwrap();
-        msg.channel_id.send_message(|m| {
-            m.embed(|_| {
-                display_server(&server)
-                    .title("Roles set (don't forget to commit)")
-                    .color(Colour::from_rgb(120, 17, 176))
-            })
-        })?;
+        // Once we get here there *is* a draft in the map so it's safe to unwrap.
+        let server = &incomplete_servers
+            .get(&(server_id, msg.author.id))
+            .unwrap()
+            .server;
+        send_embeds(
+            msg.channel_id,
+            display_server(server_id, server, tr(Some(server_id), "pm.roles.title")),
+        )?;
 
         Ok(())
     }
@@ -299,10 +406,8 @@ impl Command for AddGameCommand {
         };
 
         {
-            let mut incomplete_servers = INCOMPLETE_SERVERS
-                .write()
-                .expect("couldn't lock INCOMPLETE_SERVERS for writing");
-            let server = incomplete_servers.entry(server_id).or_default();
+            let mut incomplete_servers = write_drafts();
+            let server = draft_mut(&mut incomplete_servers, (server_id, msg.author.id));
             server.games.push(game);
         }
 
@@ -311,25 +416,107 @@ impl Command for AddGameCommand {
         let incomplete_servers = INCOMPLETE_SERVERS
             .read()
             .expect("couldn't lock INCOMPLETE_SERVERS for reading");
-        // Once we get here there *is* a server in the map so it's safe to unwrap.
-        let server = incomplete_servers.get(&server_id).unwrap();
-        msg.channel_id.send_message(|m| {
-            m.embed(|_| {
-                display_server(&server)
-                    .title("Game added (don't forget to commit)")
-                    .color(Colour::from_rgb(120, 17, 176))
-            })
-        })?;
+        // Once we get here there *is* a draft in the map so it's safe to unwrap.
+        let server = &incomplete_servers
+            .get(&(server_id, msg.author.id))
+            .unwrap()
+            .server;
+        send_embeds(
+            msg.channel_id,
+            display_server(server_id, server, tr(Some(server_id), "pm.game.title")),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Edits the poll and result templates of the server, or previews them.
+impl Command for TemplateCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some("Edits the poll or result message template. Giving no value resets the part to the default. Texts can use the {date}, {end_time}, {count}, {game} and {players} placeholders; colours are hexadecimal. `preview` shows the message as it will be sent.".to_string());
+        options.usage =
+            Some("(poll|result) (title|description|footer|colour|thumbnail|preview) [value]".to_string());
+        options.help_available = true;
+        options.min_args = Some(2);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+        let mut args = args;
+
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+
+        let kind = args.single::<String>()?;
+        let part = args.single::<String>()?;
+        let value = Some(args.rest().to_string()).filter(|v| !v.is_empty());
+
+        if kind != "poll" && kind != "result" {
+            return Err(tr(Some(server_id), "pm.template.kind").into());
+        }
+
+        if part != "preview" {
+            let mut incomplete_servers = write_drafts();
+            let server = draft_mut(&mut incomplete_servers, (server_id, msg.author.id));
+            let template = if kind == "poll" {
+                &mut server.templates.poll
+            } else {
+                &mut server.templates.result
+            };
+            template.set(&part, value.clone()).map_err(|key| {
+                Error::InvalidArgument(tr_with(
+                    Some(server_id),
+                    key,
+                    &[("part", part.clone()), ("value", value.unwrap_or_default())],
+                ))
+            })?;
+        }
+
+        // Locking for writing to make sure a stale draft isn't previewed.
+        let incomplete_servers = write_drafts();
+        let server = incomplete_servers
+            .get(&(server_id, msg.author.id))
+            .map(|d| &d.server)
+            .ok_or_else(|| tr(Some(server_id), "pm.no_draft"))?;
+
+        let embeds = if kind == "poll" {
+            // Only the first poll message, if it's split.
+            let games = server
+                .games
+                .chunks(super::REACTIONS_PER_MESSAGE)
+                .next()
+                .unwrap_or(&[]);
+            super::poll_embeds(server_id, server, games)
+        } else {
+            // Show the first game with its own name as player, so the preview looks like a real
+            // result.
+            let game = server
+                .games
+                .first()
+                .ok_or_else(|| tr(Some(server_id), "pm.template.no_game"))?;
+            super::result_embeds(server_id, server, game, &[msg.author.mention()])
+        };
+
+        let content = if part == "preview" {
+            tr(Some(server_id), "pm.template.preview")
+        } else {
+            tr(Some(server_id), "pm.template.title")
+        };
+        msg.channel_id.send_message(|m| m.content(content))?;
+        send_embeds(msg.channel_id, embeds)?;
 
         Ok(())
     }
 }
 
-/// Saves the incomplete server to the real config list and puts it on the disk.
+/// Shows what the admin's draft changes, then saves it to the real config list and puts it on the
+/// disk once confirmed. The draft is then done with.
 impl Command for CommitCommand {
     fn options(&self) -> Arc<CommandOptions> {
         let mut options = CommandOptions::default();
-        options.desc = Some("Adds the configuration made to the live configuration and saves it to disk. Ask an owner to rehash the configuration.".to_string());
+        options.desc = Some("Shows the changes you made, then adds them to the live configuration and saves it to disk once you confirm with a reaction. The new schedule applies right away.".to_string());
         options.help_available = true;
 
         Arc::new(options)
@@ -338,66 +525,383 @@ impl Command for CommitCommand {
     fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
         // Unwrap is safe here because this command is only available in servers.
         let server_id = msg.guild_id.unwrap();
-        let incomplete_servers = INCOMPLETE_SERVERS
-            .read()
-            .expect("couldn't lock INCOMPLETE_SERVERS for reading");
-        // Once we get here there *is* a server in the map so it's safe to unwrap.
-        let server = incomplete_servers
-            .get(&server_id)
-            .ok_or("Server config not found in INCOMPLETE_SERVERS")?;
+        let key = (server_id, msg.author.id);
+        let (server, last_edit) = write_drafts()
+            .get(&key)
+            .map(|d| (d.server.clone(), d.last_edit))
+            .ok_or_else(|| tr(Some(server_id), "pm.no_draft"))?;
+        // Checked again on commit, but there's no need to confirm a draft that would be refused.
+        schedule::check_events(server_id, &server.start, &server.end)
+            .map_err(Error::InvalidArgument)?;
+
+        let mut embeds = diff_embeds(server_id, &server)
+            .ok_or_else(|| tr(Some(server_id), "pm.diff.none"))?;
+        // The confirmation goes on the last page, the others are sent before it.
+        // There is always at least one embed.
+        let last = embeds.pop().unwrap();
+        send_embeds(msg.channel_id, embeds)?;
+
+        let channel_id = msg.channel_id;
+        confirmation::ask(
+            msg,
+            tr(Some(server_id), "pm.commit.confirm"),
+            last,
+            Box::new(move || {
+                let result = commit(key, server.clone(), last_edit).and_then(|()| {
+                    let mut content = format!(
+                        "{}\n{}",
+                        tr(Some(server_id), "pm.commit.done"),
+                        super::next_events(server_id, &server)
+                    );
+                    if let Some(warning) = split_warning(server_id, &server) {
+                        content = format!("{}\n{}", content, warning);
+                    }
+                    channel_id
+                        .send_message(|m| m.content(content))
+                        .map(|_| ())
+                        .map_err(Error::from)
+                });
+                if let Err(e) = result {
+                    error::report(Some(server_id), channel_id, "commit", e);
+                }
+            }),
+        )?;
 
-        {
-            let mut config = CONFIG.write().expect("couldn't lock CONFIG for writing");
-            config.insert(server_id, server.clone());
-        }
+        Ok(())
+    }
+}
+
+/// Puts a draft in the live configuration, saves it, records it in the history and reschedules
+/// the events. The draft is thrown away, unless it was edited since `last_edit`.
+fn commit(key: DraftKey, server: Server, last_edit: Instant) -> Result<(), Error> {
+    let (server_id, author) = key;
+    // A draft without valid events would never post its polls.
+    schedule::check_events(server_id, &server.start, &server.end)
+        .map_err(Error::InvalidArgument)?;
+
+    let summary = {
+        let mut config = CONFIG.write().expect("couldn't lock CONFIG for writing");
+        let summary = diff::summary(server_id, config.get(&server_id), &server);
+        config.insert(server_id, server.clone());
+        summary
+    };
+
+    super::save_config()?;
+    super::reschedule(server_id);
+    history::record(server_id, author, summary, server)?;
+
+    let mut drafts = write_drafts();
+    if drafts.get(&key).map(|d| d.last_edit) == Some(last_edit) {
+        drafts.remove(&key);
+    }
+
+    Ok(())
+}
 
-        super::save_config()?;
+/// Shows what committing the admin's draft would change.
+impl Command for DiffCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some(
+            "Shows the differences between the live configuration and the one you're editing."
+                .to_string(),
+        );
+        options.help_available = true;
+        options.max_args = Some(0);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+        let server = write_drafts()
+            .get(&(server_id, msg.author.id))
+            .map(|d| d.server.clone())
+            .ok_or_else(|| tr(Some(server_id), "pm.no_draft"))?;
+
+        match diff_embeds(server_id, &server) {
+            Some(embeds) => send_paginated(msg, embeds)?,
+            None => msg
+                .channel_id
+                .send_message(|m| m.content(tr(Some(server_id), "pm.diff.none")))?,
+        };
+
+        Ok(())
+    }
+}
+
+/// The differences between the live configuration and a draft, or `None` if there aren't any.
+fn diff_embeds(server_id: GuildId, server: &Server) -> Option<Vec<CreateEmbed>> {
+    let sections = {
+        let config = CONFIG.read().expect("couldn't lock CONFIG for reading");
+        diff::server_diff(server_id, config.get(&server_id), server)
+    };
+    if sections.is_empty() {
+        return None;
+    }
+
+    let base = CreateEmbed::default()
+        .title(tr(Some(server_id), "pm.diff.title"))
+        .color(Colour(server.templates.colour()));
+    Some(paginate_embed(&base, sections, &tr(Some(server_id), "cont")))
+}
+
+/// Throws away the admin's draft.
+impl Command for DiscardCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some("Throws away the configuration you're editing.".to_string());
+        options.help_available = true;
+        options.max_args = Some(0);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+
+        write_drafts()
+            .remove(&(server_id, msg.author.id))
+            .ok_or_else(|| tr(Some(server_id), "pm.no_draft"))?;
 
         msg.channel_id
-            .send_message(|m| m.content("Configuration saved and written to disk."))?;
+            .send_message(|m| m.content(tr(Some(server_id), "pm.discard.done")))?;
         Ok(())
     }
 }
 
-#[inline]
-/// Useful function for displaying a server config.
-/// BE CAREFUL: if there are too many roles, it will create an invalid message!
-/// @TODO use the fold_by_strlen function
-/// @DRY there's some code dupe here
-fn display_server(server: &Server) -> CreateEmbed {
-    CreateEmbed::default()
-        .field("Channel", server.channel_id.mention(), false)
-        .field(
-            "Event times",
-            format!("Starts: {}\n  Ends: {}\n", &server.start, &server.end),
-            true,
-        ).field(
-            "Roles",
-            match server.role_ids {
-                None => "None".to_string(),
-                Some(ref roles) => roles
-                    .iter()
-                    .map(|r| r.mention())
-                    .collect::<Vec<String>>()
-                    .join(", "),
-            },
-            false,
-        ).fields(server.games.iter().map(|g| {
+/// Uploads the live configuration of the server as a JSON file.
+impl Command for ExportCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some(
+            "Uploads the configuration of this server as a file, to be edited and imported back."
+                .to_string(),
+        );
+        options.help_available = true;
+        options.max_args = Some(0);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+
+        let data = {
+            let config = CONFIG.read().expect("couldn't lock CONFIG for reading");
+            let server = config.get(&server_id).ok_or(Error::MissingConfig)?;
+            serde_json::to_vec_pretty(server)?
+        };
+
+        let filename = format!("premade_creator_{}.json", server_id);
+        msg.channel_id.send_files(vec![(&data[..], &filename[..])], |m| {
+            m.content(tr(Some(server_id), "pm.export.done"))
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Loads an attached JSON file in the admin's draft.
+impl Command for ImportCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some("Loads the attached configuration file (like the ones `pmconfig export` makes) for review. It's applied once committed.".to_string());
+        options.help_available = true;
+        options.max_args = Some(0);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+
+        let attachment = msg
+            .attachments
+            .first()
+            .ok_or_else(|| tr(Some(server_id), "pm.import.no_file"))?;
+        if attachment.size > MAX_IMPORT_SIZE {
+            return Err(tr(Some(server_id), "pm.import.too_big").into());
+        }
+        let data = attachment.download()?;
+
+        let server = parse_server(server_id, &data)?;
+        validate(server_id, &server)?;
+
+        send_paginated(
+            msg,
+            display_server(server_id, &server, tr(Some(server_id), "pm.import.title")),
+        )?;
+
+        let key = (server_id, msg.author.id);
+        write_drafts().insert(key, Draft::new(server));
+        if let Some(warning) = other_editors(key) {
+            msg.channel_id.send_message(|m| m.content(warning))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads an uploaded configuration. It's either a server, or servers by ID like in the config
+/// file; in that case, the one of this server is taken, or the only one there is.
+fn parse_server(server_id: GuildId, data: &[u8]) -> Result<Server, String> {
+    let invalid = |e: serde_json::Error| {
+        tr_with(
+            Some(server_id),
+            "pm.import.invalid",
+            &[("error", e.to_string())],
+        )
+    };
+
+    let value: Value = serde_json::from_slice(data).map_err(invalid)?;
+    if value.get("channel_id").is_some() {
+        return serde_json::from_value(value).map_err(invalid);
+    }
+
+    let mut servers: HashMap<GuildId, Server> = serde_json::from_value(value).map_err(invalid)?;
+    if let Some(server) = servers.remove(&server_id) {
+        return Ok(server);
+    }
+    if servers.len() == 1 {
+        // Unwrap is safe since there's exactly one server.
+        return Ok(servers.into_iter().next().unwrap().1);
+    }
+
+    Err(tr(Some(server_id), "pm.import.which"))
+}
+
+/// Checks a configuration that didn't go through the editing commands.
+fn validate(server_id: GuildId, server: &Server) -> Result<(), String> {
+    schedule::check_events(server_id, &server.start, &server.end)?;
+
+    for (i, game) in server.games.iter().enumerate() {
+        let others = &server.games[..i];
+        if others.iter().any(|g| g.name == game.name) {
+            return Err(tr_with(
+                Some(server_id),
+                "pm.import.duplicate_game",
+                &[("game", game.name.clone())],
+            ));
+        }
+        if others.iter().any(|g| g.emoji == game.emoji) {
+            return Err(tr_with(
+                Some(server_id),
+                "pm.import.duplicate_emoji",
+                &[("emoji", game.emoji.to_string())],
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Useful function for displaying a server config, in as many embeds as needed.
+pub fn display_server(server_id: GuildId, server: &Server, title: String) -> Vec<CreateEmbed> {
+    paginate_embed(
+        &server_embed(server_id, server, title),
+        server_sections(server_id, server),
+        &tr(Some(server_id), "cont"),
+    )
+}
+
+/// The embed the sections of a server config are put in.
+fn server_embed(server_id: GuildId, server: &Server, title: String) -> CreateEmbed {
+    let base = CreateEmbed::default()
+        .title(title)
+        .color(Colour(server.templates.colour()));
+    match split_warning(server_id, server) {
+        Some(warning) => base.description(warning),
+        None => base,
+    }
+}
+
+/// Lists the next polls of a config.
+fn next_polls_section(server_id: GuildId, polls: &[schedule::Poll]) -> Section {
+    let format = |time: &DateTime<Utc>| time.format("%Y-%m-%d %H:%M UTC").to_string();
+    Section::new(
+        tr(Some(server_id), "pm.display.next"),
+        polls
+            .iter()
+            .map(|(start, end)| format!("{} → {}", format(start), format(end)))
+            .collect(),
+    )
+}
+
+/// Warns that the poll will be split in several messages, if it has too many games for one.
+fn split_warning(server_id: GuildId, server: &Server) -> Option<String> {
+    let count = server.games.len();
+    if count <= super::REACTIONS_PER_MESSAGE {
+        return None;
+    }
+
+    Some(tr_with(
+        Some(server_id),
+        "pm.display.split",
+        &[
+            ("count", count.to_string()),
+            ("max", super::REACTIONS_PER_MESSAGE.to_string()),
             (
-                format!("{} {}", g.emoji, g.name),
-                format!(
-                    "In {}, {}",
-                    g.channel_id.mention(),
-                    match g.role_ids {
-                        None => "None".to_string(),
-                        Some(ref roles) => roles
-                            .iter()
-                            .map(|r| r.mention())
-                            .collect::<Vec<String>>()
-                            .join(", "),
-                    }
-                ),
-                false,
-            )
-        }))
+                "messages",
+                server
+                    .games
+                    .chunks(super::REACTIONS_PER_MESSAGE)
+                    .len()
+                    .to_string(),
+            ),
+        ],
+    ))
+}
+
+/// A schedule in plain terms if possible, or as is.
+fn describe_schedule(expr: &str) -> String {
+    schedule::describe(expr).unwrap_or_else(|| format!("`{}`", expr))
+}
+
+/// Describes a server config as embed sections.
+fn server_sections(server_id: GuildId, server: &Server) -> Vec<Section> {
+    let none = tr(Some(server_id), "none");
+    let roles = |roles: &Option<Vec<RoleId>>| match roles {
+        Some(ref roles) if !roles.is_empty() => roles.iter().map(|r| r.mention()).collect(),
+        _ => vec![none.clone()],
+    };
+
+    let mut sections = vec![
+        Section::new(
+            tr(Some(server_id), "pm.display.channel"),
+            vec![server.channel_id.mention()],
+        ),
+        Section::new(
+            tr(Some(server_id), "pm.display.times"),
+            vec![tr_with(
+                Some(server_id),
+                "pm.display.times_value",
+                &[
+                    ("start", describe_schedule(&server.start)),
+                    ("end", describe_schedule(&server.end)),
+                ],
+            )],
+        ).inline(true),
+        Section::new(tr(Some(server_id), "pm.display.roles"), roles(&server.role_ids))
+            .separator(", "),
+    ];
+    sections.extend(server.games.iter().map(|g| {
+        Section::new(
+            format!("{} {}", g.emoji, g.name),
+            vec![tr_with(
+                Some(server_id),
+                "pm.display.game",
+                &[
+                    ("channel", g.channel_id.mention()),
+                    ("roles", roles(&g.role_ids).join(", ")),
+                ],
+            )],
+        )
+    }));
+
+    sections
 }
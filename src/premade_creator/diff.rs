@@ -0,0 +1,288 @@
+//! Differences between the live configuration of a server and a draft, shown before committing.
+//!
+//! Games are matched by name: a renamed game shows as removed and added.
+
+use serenity::model::misc::Mentionable;
+use serenity::model::prelude::*;
+
+use i18n::{tr, tr_with};
+use utils::Section;
+
+use super::{GameInfo, Server};
+
+/// Roles as they are mentioned: no roles means nobody, an empty list means everyone.
+fn role_mentions(roles: &Option<Vec<RoleId>>) -> Vec<String> {
+    match roles {
+        Some(ref ids) if ids.is_empty() => vec!["@everyone".to_string()],
+        Some(ref ids) => ids.iter().map(|r| r.mention()).collect(),
+        None => Vec::new(),
+    }
+}
+
+/// "old → new" for a value, `old` being "None" if there's no live configuration.
+fn change(server_id: GuildId, key: &str, old: Option<String>, new: String) -> String {
+    tr_with(
+        Some(server_id),
+        key,
+        &[
+            ("old", old.unwrap_or_else(|| tr(Some(server_id), "none"))),
+            ("new", new),
+        ],
+    )
+}
+
+/// Lines describing what changes in a game, or nothing if it doesn't.
+fn game_changes(server_id: GuildId, old: &GameInfo, new: &GameInfo) -> Vec<String> {
+    let none = tr(Some(server_id), "none");
+    let roles = |roles: &Option<Vec<RoleId>>| {
+        let mentions = role_mentions(roles);
+        if mentions.is_empty() {
+            none.clone()
+        } else {
+            mentions.join(", ")
+        }
+    };
+
+    let mut changes = Vec::new();
+    if old.emoji != new.emoji {
+        changes.push(change(
+            server_id,
+            "pm.diff.game.emoji",
+            Some(old.emoji.to_string()),
+            new.emoji.to_string(),
+        ));
+    }
+    if old.channel_id != new.channel_id {
+        changes.push(change(
+            server_id,
+            "pm.diff.game.channel",
+            Some(old.channel_id.mention()),
+            new.channel_id.mention(),
+        ));
+    }
+    if old.role_ids != new.role_ids {
+        changes.push(change(
+            server_id,
+            "pm.diff.game.roles",
+            Some(roles(&old.role_ids)),
+            roles(&new.role_ids),
+        ));
+    }
+
+    changes
+}
+
+/// Describes what committing `draft` changes to `live` (`None` if the server has no live
+/// configuration yet). Sections without changes are left out, so no sections means no changes.
+pub fn server_diff(server_id: GuildId, live: Option<&Server>, draft: &Server) -> Vec<Section> {
+    changes(server_id, live, draft)
+        .into_iter()
+        .map(|(name, lines)| Section::new(name, lines))
+        .collect()
+}
+
+/// The same changes as `server_diff`, as a flat list of lines.
+pub fn summary(server_id: GuildId, live: Option<&Server>, draft: &Server) -> Vec<String> {
+    changes(server_id, live, draft)
+        .into_iter()
+        .flat_map(|(_, lines)| lines)
+        .collect()
+}
+
+/// The changes, as (section name, lines) pairs. Sections without lines are left out.
+fn changes(
+    server_id: GuildId,
+    live: Option<&Server>,
+    draft: &Server,
+) -> Vec<(String, Vec<String>)> {
+    let mut settings = Vec::new();
+    if live.map(|l| l.channel_id) != Some(draft.channel_id) {
+        settings.push(change(
+            server_id,
+            "pm.diff.channel",
+            live.map(|l| l.channel_id.mention()),
+            draft.channel_id.mention(),
+        ));
+    }
+    if live.map(|l| &l.start) != Some(&draft.start) {
+        settings.push(change(
+            server_id,
+            "pm.diff.start",
+            live.map(|l| l.start.clone()),
+            draft.start.clone(),
+        ));
+    }
+    if live.map(|l| &l.end) != Some(&draft.end) {
+        settings.push(change(
+            server_id,
+            "pm.diff.end",
+            live.map(|l| l.end.clone()),
+            draft.end.clone(),
+        ));
+    }
+    let old_roles = live.map(|l| role_mentions(&l.role_ids)).unwrap_or_default();
+    let new_roles = role_mentions(&draft.role_ids);
+    settings.extend(new_roles.iter().filter(|r| !old_roles.contains(r)).map(|r| {
+        tr_with(Some(server_id), "pm.diff.role_added", &[("role", r.clone())])
+    }));
+    settings.extend(old_roles.iter().filter(|r| !new_roles.contains(r)).map(|r| {
+        tr_with(Some(server_id), "pm.diff.role_removed", &[("role", r.clone())])
+    }));
+
+    let old_games = live.map(|l| &l.games[..]).unwrap_or(&[]);
+    let mut games = Vec::new();
+    for game in &draft.games {
+        let name = format!("{} {}", game.emoji, game.name);
+        match old_games.iter().find(|g| g.name == game.name) {
+            None => games.push(tr_with(
+                Some(server_id),
+                "pm.diff.game_added",
+                &[("game", name)],
+            )),
+            Some(old) => {
+                let changes = game_changes(server_id, old, game);
+                if !changes.is_empty() {
+                    games.push(tr_with(
+                        Some(server_id),
+                        "pm.diff.game_changed",
+                        &[("game", name), ("changes", changes.join(", "))],
+                    ));
+                }
+            }
+        }
+    }
+    games.extend(
+        old_games
+            .iter()
+            .filter(|old| draft.games.iter().all(|g| g.name != old.name))
+            .map(|old| {
+                tr_with(
+                    Some(server_id),
+                    "pm.diff.game_removed",
+                    &[("game", format!("{} {}", old.emoji, old.name))],
+                )
+            }),
+    );
+
+    let mut templates = Vec::new();
+    let default_templates = Default::default();
+    let old_templates = live.map_or(&default_templates, |l| &l.templates);
+    if old_templates.poll != draft.templates.poll {
+        templates.push(tr(Some(server_id), "pm.diff.poll_template"));
+    }
+    if old_templates.result != draft.templates.result {
+        templates.push(tr(Some(server_id), "pm.diff.result_template"));
+    }
+
+    vec![
+        (tr(Some(server_id), "pm.diff.settings"), settings),
+        (tr(Some(server_id), "pm.diff.games"), games),
+        (tr(Some(server_id), "pm.diff.templates"), templates),
+    ].into_iter()
+    .filter(|(_, lines)| !lines.is_empty())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER: GuildId = GuildId(0);
+
+    fn game(name: &str, emoji: &str, channel: u64) -> GameInfo {
+        GameInfo {
+            name: name.to_string(),
+            emoji: ReactionType::Unicode(emoji.to_string()),
+            role_ids: None,
+            channel_id: ChannelId(channel),
+        }
+    }
+
+    fn server() -> Server {
+        Server {
+            channel_id: ChannelId(1),
+            start: "0 0 19 * * *".to_string(),
+            end: "0 0 21 * * *".to_string(),
+            role_ids: Some(vec![RoleId(10)]),
+            games: vec![game("Échecs", "♟", 2), game("Go", "⚫", 2)],
+            templates: Default::default(),
+        }
+    }
+
+    #[test]
+    fn same_configurations_have_no_changes() {
+        assert!(changes(SERVER, Some(&server()), &server()).is_empty());
+    }
+
+    #[test]
+    fn everything_is_new_without_a_live_configuration() {
+        let changes = changes(SERVER, None, &server());
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes[0],
+            (
+                "Settings".to_string(),
+                vec![
+                    "Channel: None → <#1>".to_string(),
+                    "Start: `None` → `0 0 19 * * *`".to_string(),
+                    "End: `None` → `0 0 21 * * *`".to_string(),
+                    "+ role <@&10>".to_string(),
+                ]
+            )
+        );
+        assert_eq!(
+            changes[1],
+            (
+                "Games".to_string(),
+                vec!["+ ♟ Échecs".to_string(), "+ ⚫ Go".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn games_are_matched_by_name() {
+        let live = server();
+        let mut draft = server();
+        draft.games[0].emoji = ReactionType::Unicode("♞".to_string());
+        draft.games[0].channel_id = ChannelId(3);
+        draft.games[1].name = "Gō".to_string();
+
+        let changes = changes(SERVER, Some(&live), &draft);
+        assert_eq!(
+            changes,
+            vec![(
+                "Games".to_string(),
+                vec![
+                    "~ ♞ Échecs: emoji ♟ → ♞, channel <#2> → <#3>".to_string(),
+                    "+ ⚫ Gō".to_string(),
+                    "- ⚫ Go".to_string(),
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn roles_and_templates_changes_are_listed() {
+        let live = server();
+        let mut draft = server();
+        // An empty list pings everyone.
+        draft.role_ids = Some(Vec::new());
+        draft.templates.poll.set("title", Some("Ce soir".to_string())).unwrap();
+
+        let changes = changes(SERVER, Some(&live), &draft);
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "Settings".to_string(),
+                    vec!["+ role @everyone".to_string(), "- role <@&10>".to_string()]
+                ),
+                (
+                    "Templates".to_string(),
+                    vec!["Poll template modified".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(summary(SERVER, Some(&live), &draft).len(), 3);
+    }
+}
@@ -0,0 +1,174 @@
+//! Keeps the live configurations consistent when what they use is deleted.
+//!
+//! * A deleted role is removed from the roles to mention.
+//! * A deleted channel removes the games posted in it. If it was the channel of the polls,
+//!   nothing can be done but telling the owner of the server.
+//! * When the bot leaves a server (or gets kicked), its configuration is removed and the owners
+//!   of the bot are told.
+//!
+//! Every change is recorded in the history, so it can be rolled back, and the owner of the server
+//! gets a summary in a private message.
+
+use serenity::builder::*;
+use serenity::model::misc::Mentionable;
+use serenity::model::prelude::*;
+use serenity::utils::*;
+use serenity::CACHE;
+
+use i18n::{tr, tr_with};
+use utils::*;
+use SETTINGS;
+
+use super::check;
+use super::diff;
+use super::history;
+use super::{Server, CONFIG};
+
+/// Applies a change to the live configuration of a server, if it has one. `change` returns
+/// whether it changed anything.
+fn update<F: FnOnce(&mut Server) -> bool>(server_id: GuildId, reason: String, change: F) {
+    let (summary, server) = {
+        let mut config = CONFIG.write().expect("couldn't lock CONFIG for writing");
+        let server = match config.get_mut(&server_id) {
+            Some(server) => server,
+            None => return,
+        };
+        let before = server.clone();
+        if !change(server) {
+            return;
+        }
+        (diff::summary(server_id, Some(&before), server), server.clone())
+    };
+    info!("Config of server {} updated: {}", server_id, reason);
+
+    if let Err(e) = super::save_config() {
+        warn!("Couldn't save config: {:?}", e);
+    }
+    let mut lines = vec![reason];
+    lines.extend(summary);
+    let bot_id = CACHE.read().user.id;
+    if let Err(e) = history::record(server_id, bot_id, lines.clone(), server) {
+        warn!("Couldn't save config history: {:?}", e);
+    }
+
+    tell_owner(server_id, lines);
+}
+
+/// Tells the owner of a server what happened to its configuration.
+fn tell_owner(server_id: GuildId, lines: Vec<String>) {
+    let base = CreateEmbed::default()
+        .title(tr(Some(server_id), "events.title"))
+        .color(Colour::ORANGE);
+    let embeds = paginate_embed(
+        &base,
+        vec![Section::new(tr(Some(server_id), "events.changes"), lines)],
+        &tr(Some(server_id), "cont"),
+    );
+    check::tell_owner(server_id, embeds);
+}
+
+/// Removes a deleted role from a configuration.
+pub fn role_deleted(server_id: GuildId, role_id: RoleId) {
+    let reason = tr_with(
+        Some(server_id),
+        "events.role_deleted",
+        &[("role", role_id.to_string())],
+    );
+    update(server_id, reason, |server| {
+        // No roles means nobody gets mentioned, but an empty list means everyone: when the last
+        // role is removed, there are no roles anymore.
+        let remove = |roles: &mut Option<Vec<RoleId>>| match roles.take() {
+            Some(mut ids) => {
+                let count = ids.len();
+                ids.retain(|&r| r != role_id);
+                let removed = ids.len() != count;
+                *roles = if removed && ids.is_empty() {
+                    None
+                } else {
+                    Some(ids)
+                };
+                removed
+            }
+            None => false,
+        };
+
+        let mut removed = remove(&mut server.role_ids);
+        for game in &mut server.games {
+            removed |= remove(&mut game.role_ids);
+        }
+        removed
+    });
+}
+
+/// Removes the games of a deleted channel from a configuration.
+pub fn channel_deleted(server_id: GuildId, channel_id: ChannelId) {
+    let polls_channel = {
+        let config = CONFIG.read().expect("couldn't lock CONFIG for reading");
+        match config.get(&server_id) {
+            Some(server) => server.channel_id == channel_id,
+            None => return,
+        }
+    };
+    if polls_channel {
+        warn!(
+            "The poll channel {} of server {} was deleted",
+            channel_id, server_id
+        );
+        tell_owner(
+            server_id,
+            vec![tr(Some(server_id), "events.polls_channel_deleted")],
+        );
+    }
+
+    let reason = tr_with(
+        Some(server_id),
+        "events.channel_deleted",
+        &[("channel", format!("{} ({})", channel_id.mention(), channel_id))],
+    );
+    update(server_id, reason, |server| {
+        let count = server.games.len();
+        server.games.retain(|g| g.channel_id != channel_id);
+        server.games.len() != count
+    });
+}
+
+/// Forgets the configuration of a server the bot isn't in anymore.
+pub fn guild_deleted(server_id: GuildId) {
+    let removed = {
+        let mut config = CONFIG.write().expect("couldn't lock CONFIG for writing");
+        config.remove(&server_id).is_some()
+    };
+    if !removed {
+        return;
+    }
+    info!("Removed from server {}, its config is removed", server_id);
+    check::forget_status(server_id);
+
+    if let Err(e) = super::save_config() {
+        warn!("Couldn't save config: {:?}", e);
+    }
+    super::reschedule(server_id);
+
+    // The server is gone, so its owner can't be found: tell the owners of the bot.
+    let owners = {
+        let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+        settings.get_array("owners").unwrap_or_default()
+    };
+    let content = tr_with(
+        None,
+        "events.guild_deleted",
+        &[("server", server_id.to_string())],
+    );
+    for owner in owners {
+        let owner = match owner.try_into::<u64>() {
+            Ok(owner) => UserId(owner),
+            Err(_) => continue,
+        };
+        let result = owner
+            .create_dm_channel()
+            .and_then(|dm| dm.id.send_message(|m| m.content(&content)));
+        if let Err(e) = result {
+            warn!("Couldn't send a message to owner {}: {:?}", owner, e);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Revisions of the server configurations.
+//!
+//! Every commit and every rollback is stored as a numbered revision in
+//! `data/premade_creator_history.json`, with its author, its date and a summary of its changes,
+//! so that an older configuration can be restored with `pmconfig rollback`. Only the last
+//! `HISTORY_SIZE` revisions of each server are kept.
+
+use chrono::prelude::*;
+
+use serenity::builder::*;
+use serenity::framework::standard::*;
+use serenity::model::misc::Mentionable;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::*;
+
+use serde_json::{from_reader, to_writer_pretty};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use error::Error;
+use guild_log::{self, Level};
+use i18n::{tr, tr_with};
+use paginator::send_paginated;
+use utils::*;
+
+use super::diff;
+use super::schedule;
+use super::template;
+use super::Server;
+use super::CONFIG;
+
+/// Number of revisions kept per server.
+const HISTORY_SIZE: usize = 50;
+
+lazy_static! {
+    static ref HISTORY: RwLock<HashMap<GuildId, Vec<Revision>>> = {
+        RwLock::new(initialize_history())
+    };
+}
+
+/// A configuration as it was committed.
+#[derive(Clone, Serialize, Deserialize)]
+struct Revision {
+    number: u32,
+    author: UserId,
+    time: DateTime<Utc>,
+    summary: Vec<String>,
+    server: Server,
+}
+
+fn initialize_history() -> HashMap<GuildId, Vec<Revision>> {
+    let file = File::open("data/premade_creator_history.json");
+    if file.is_err() {
+        return HashMap::new();
+    }
+    let file = file.unwrap();
+    from_reader(file).unwrap_or_else(|e| {
+        warn!("couldn't deserialize premade_creator history: {:?}", e);
+        HashMap::new()
+    })
+}
+
+fn save_history() -> Result<(), Error> {
+    let file = File::create("data/premade_creator_history.json")?;
+
+    let history = HISTORY.read().expect("couldn't lock HISTORY for reading");
+    to_writer_pretty(file, &*history)?;
+    Ok(())
+}
+
+/// Stores a new revision of a server's configuration, and returns its number.
+pub fn record(
+    server_id: GuildId,
+    author: UserId,
+    summary: Vec<String>,
+    server: Server,
+) -> Result<u32, Error> {
+    let summary_lines = summary.join("\n");
+    let number = {
+        let mut history = HISTORY.write().expect("couldn't lock HISTORY for writing");
+        let revisions = history.entry(server_id).or_default();
+        let number = revisions.last().map_or(1, |r| r.number + 1);
+        revisions.push(Revision {
+            number,
+            author,
+            time: Utc::now(),
+            summary,
+            server,
+        });
+        if revisions.len() > HISTORY_SIZE {
+            let extra = revisions.len() - HISTORY_SIZE;
+            revisions.drain(..extra);
+        }
+        number
+    };
+
+    save_history()?;
+    guild_log::post(
+        server_id,
+        Level::Info,
+        tr_with(
+            Some(server_id),
+            "log.revision",
+            &[
+                ("number", number.to_string()),
+                ("author", author.mention()),
+                ("summary", summary_lines),
+            ],
+        ),
+    );
+    Ok(number)
+}
+
+#[derive(Default)]
+pub struct HistoryCommand;
+#[derive(Default)]
+pub struct RollbackCommand;
+
+/// Lists the revisions of the server, the latest first.
+impl Command for HistoryCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some("Lists the committed configurations of this server.".to_string());
+        options.help_available = true;
+        options.max_args = Some(0);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+
+        let sections = {
+            let history = HISTORY.read().expect("couldn't lock HISTORY for reading");
+            let revisions = history.get(&server_id).map_or(&[][..], |r| &r[..]);
+            revisions
+                .iter()
+                .rev()
+                .map(|r| {
+                    let name = tr_with(
+                        Some(server_id),
+                        "pm.history.revision",
+                        &[
+                            ("number", r.number.to_string()),
+                            ("date", r.time.format("%Y-%m-%d %H:%M UTC").to_string()),
+                        ],
+                    );
+                    let mut lines = vec![tr_with(
+                        Some(server_id),
+                        "pm.history.author",
+                        &[("author", r.author.mention())],
+                    )];
+                    lines.extend(r.summary.iter().cloned());
+                    Section::new(name, lines)
+                }).collect::<Vec<Section>>()
+        };
+        if sections.is_empty() {
+            return Err(tr(Some(server_id), "pm.history.empty").into());
+        }
+
+        let base = CreateEmbed::default()
+            .title(tr(Some(server_id), "pm.history.title"))
+            .color(Colour(template::DEFAULT_COLOUR));
+        send_paginated(
+            msg,
+            paginate_embed(&base, sections, &tr(Some(server_id), "cont")),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Puts an older revision back in the live configuration, as a new revision.
+impl Command for RollbackCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some(
+            "Restores a committed configuration of this server (see `pmconfig history`)."
+                .to_string(),
+        );
+        options.usage = Some("<revision>".to_string());
+        options.help_available = true;
+        options.min_args = Some(1);
+        options.max_args = Some(1);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+        let mut args = args;
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+        let number = args.single::<u32>()?;
+
+        let server = {
+            let history = HISTORY.read().expect("couldn't lock HISTORY for reading");
+            history
+                .get(&server_id)
+                .and_then(|revisions| revisions.iter().find(|r| r.number == number))
+                .map(|r| r.server.clone())
+                .ok_or_else(|| {
+                    tr_with(
+                        Some(server_id),
+                        "pm.history.not_found",
+                        &[("number", number.to_string())],
+                    )
+                })?
+        };
+
+        // Revisions saved before the schedules were checked can be broken.
+        schedule::check_events(server_id, &server.start, &server.end)
+            .map_err(Error::InvalidArgument)?;
+
+        let mut summary = vec![tr_with(
+            Some(server_id),
+            "pm.history.rollback",
+            &[("number", number.to_string())],
+        )];
+        {
+            let mut config = CONFIG.write().expect("couldn't lock CONFIG for writing");
+            summary.extend(diff::summary(server_id, config.get(&server_id), &server));
+            config.insert(server_id, server.clone());
+        }
+        super::save_config()?;
+        super::reschedule(server_id);
+        let next_events = super::next_events(server_id, &server);
+        let revision = record(server_id, msg.author.id, summary, server)?;
+
+        let content = tr_with(
+            Some(server_id),
+            "pm.history.restored",
+            &[
+                ("number", number.to_string()),
+                ("revision", revision.to_string()),
+            ],
+        );
+        msg.channel_id
+            .send_message(|m| m.content(format!("{}\n{}", content, next_events)))?;
+
+        Ok(())
+    }
+}
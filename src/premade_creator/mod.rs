@@ -6,18 +6,41 @@
 //! under `premade-creator.games` (as key-value pairs, game name -> [emoji*, team size]).
 //! At the end event, it will look for reactions on the message posted for the start, and send a
 //! message with all the people who reacted, and mention the specific roles.
+//! Since a message can only hold 20 different reactions, the poll is split in as many messages as
+//! needed, 20 games each; the reactions are gathered from all of them.
 //! The job scheduler will check every `premade-creator.tick` seconds (int) for the events.
 //! "specific roles" are stored individually for each server. If no role is specified, no mention
 //! gets sent.
 //! * an emoji is either a string (for unicode emojis) or an array [name, id].
 //!
+//! The configuration is edited with the `pmconfig` commands. Every admin edits their own draft,
+//! which is thrown away when it's committed, discarded, or left untouched for
+//! `premade-creator.draft_lifetime` seconds (an hour by default).
+//!
+//! `pmconfig wizard` sets a configuration up by asking questions (see the `wizard` module).
+//! `pmconfig export` uploads the configuration of a server as a JSON file, and `pmconfig import`
+//! loads such a file (or one with servers by ID, like the example below) into a draft.
+//!
+//! `pmconfig check` looks for deleted channels, roles and emojis and missing permissions; this is
+//! also done daily for every server (see the `check` module).
+//!
+//! Committing a configuration reschedules the events of the server right away; configurations
+//! whose events don't pass the checks of the `schedule` module are refused.
+//! Deleted channels and roles are removed from the configurations (see the `events` module).
+//! Every commit is kept as a revision (see the `history` module) and can be rolled back to.
+//!
+//! The text, colour and pictures of the poll and result messages can be changed per server with
+//! an optional `"templates"` object (see the `template` module).
+//!
 //! Configuration example:
 //! ```json
 //! {
 //!     "359818298067779584": {                 // Server ID, as a string
 //!         "channel_id": 376355712223412225,   // Channel ID, as a number
 //!         "start": "0  * * * * *",            // Like cron, but with an additional number for
-//!                                             // seconds (here, at second 0 of every minute)
+//!                                             // seconds (here, at second 0 of every minute).
+//!                                             // The commands also take plain schedules (see
+//!                                             // the `schedule` module).
 //!         "end":   "30 * * * * *",            // Same
 //!         "games": [{
 //!             "name": "légoléjande",              // Game name
@@ -41,6 +64,8 @@
 //! }
 //! ```
 
+use chrono::{DateTime, Utc};
+use cron;
 use job_scheduler::{Job, JobScheduler};
 
 use serenity::builder::*;
@@ -53,21 +78,35 @@ use serenity::utils::Colour;
 
 use serde_json::{from_reader, to_writer_pretty};
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::sync::mpsc::*;
 use std::sync::RwLock;
 use std::thread;
 use std::time::Duration;
 
+use blocklist;
+use dispatch;
 use dorothy::Module;
+use error::Error;
+use guild_log::{self, Level};
+use i18n::{self, tr, tr_with};
+use modules;
 use utils::*;
 use SETTINGS;
 
+mod check;
 mod creator_command;
+mod diff;
+mod events;
+mod history;
+mod schedule;
+mod skip;
+mod template;
+mod wizard;
 
 lazy_static! {
-    static ref STATE: RwLock<HashMap<ChannelId, MessageId>> = {
+    static ref STATE: RwLock<HashMap<ChannelId, Vec<MessageId>>> = {
         // @TUNE Change the number of reserved slots
         // Keeping the space for 500 message IDs is really inexpensive memory-wise (a few
         // kilobytes, maybe a couple dozen kb with the hashmap overhead AT MOST).
@@ -77,9 +116,27 @@ lazy_static! {
     static ref CONFIG: RwLock<HashMap<GuildId, Server>> = {
         RwLock::new(initialize_config())
     };
+
+    /// Servers whose last start event didn't open a poll (skipped, blocked, without games...), so
+    /// that the end event doesn't take the missing poll for a lost one.
+    static ref WITHOUT_POLL: RwLock<HashSet<GuildId>> = RwLock::new(HashSet::new());
 }
 
-static mut SCHED_CHANNEL_TX: Option<Sender<()>> = None;
+/// Name of the module.
+pub const NAME: &str = "premade-creator";
+
+/// Discord doesn't allow more different reactions on a single message.
+const REACTIONS_PER_MESSAGE: usize = 20;
+
+static mut SCHED_CHANNEL_TX: Option<Sender<Reschedule>> = None;
+
+/// Jobs the scheduler thread must rebuild.
+enum Reschedule {
+    /// The ones of a server whose configuration changed or was removed.
+    Server(GuildId),
+    /// The ones of every server, after the configuration was reloaded.
+    All,
+}
 
 fn initialize_config() -> HashMap<GuildId, Server> {
     // @TUNE number of reserved spaces in struct
@@ -97,54 +154,132 @@ fn initialize_config() -> HashMap<GuildId, Server> {
 }
 
 fn rehash(_: &mut Context, msg: &Message, _: Args) -> Result<(), CommandError> {
-    let mut config = CONFIG.write().expect("couldn't lock config for writing");
-    *config = initialize_config();
+    {
+        let mut config = CONFIG.write().expect("couldn't lock config for writing");
+        *config = initialize_config();
+    }
+
+    reschedule_all();
+
+    msg.channel_id
+        .send_message(|m| m.content(tr(msg.guild_id, "pm.rehash.done")))?;
+
+    Ok(())
+}
+
+/// Makes the scheduler thread rebuild the jobs of a server from the live config. The jobs of
+/// the other servers are kept, so that none of their events is missed.
+fn reschedule(server_id: GuildId) {
+    send_reschedule(Reschedule::Server(server_id));
+}
+
+/// Makes the scheduler thread rebuild the jobs of every server from the live config.
+fn reschedule_all() {
+    send_reschedule(Reschedule::All);
+}
 
+fn send_reschedule(reschedule: Reschedule) {
     unsafe {
         if let Some(ref s) = SCHED_CHANNEL_TX {
-            // The other end NEEDS to be connected to work correctly. We can unwrap since it should
-            // always be the case.
-            s.send(()).unwrap();
+            if s.send(reschedule).is_err() {
+                warn!("The scheduler thread is gone, the events aren't rescheduled");
+            }
         }
     }
-    
-    msg.channel_id.send_message(|m| m.content("Configuration successfully reloaded."))?;
+}
 
-    Ok(())
+/// The jobs of a server, or `None` if its events can't be parsed.
+fn server_jobs(server_id: GuildId, server: &Server) -> Option<JobScheduler<'static>> {
+    let start = server.start.parse::<cron::Schedule>();
+    let end = server.end.parse::<cron::Schedule>();
+    let (start, end) = match (start, end) {
+        (Ok(start), Ok(end)) => (start, end),
+        (start, end) => {
+            warn!(
+                "Bad schedule in server {}, no polls: start {:?}, end {:?}",
+                server_id,
+                start.err(),
+                end.err()
+            );
+            return None;
+        }
+    };
+
+    let mut sched = JobScheduler::new();
+    sched.add(Job::new(start, move || process_start(server_id)));
+    sched.add(Job::new(end, move || process_end(server_id)));
+    Some(sched)
+}
+
+/// The jobs of every server.
+fn all_jobs() -> HashMap<GuildId, JobScheduler<'static>> {
+    let config = CONFIG.read().expect("couldn't lock config for reading");
+    config
+        .iter()
+        .filter_map(|(server_id, server)| {
+            server_jobs(*server_id, server).map(|jobs| (*server_id, jobs))
+        }).collect()
 }
 
 #[derive(Default)]
 pub struct PremadeCreator;
 
 impl Module for PremadeCreator {
-    fn register(framework: StandardFramework) -> StandardFramework {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn description(&self) -> &'static str {
+        "Polls to find players for games, at scheduled times"
+    }
+
+    fn register(&self, framework: StandardFramework) -> StandardFramework {
         let (sched_tx, sched_rx) = channel();
 
         unsafe {
             SCHED_CHANNEL_TX = Some(sched_tx);
         }
 
-        thread::spawn(move || loop {
-            let mut sched = JobScheduler::new();
-
-            {
-                let config = CONFIG.read().expect("couldn't lock config for reading");
-
-                for (server_id, server) in config.iter() {
-                    let sid = *server_id;
-                    sched.add(Job::new(
-                        server.start.parse().expect("bad start syntax"),
-                        move || process_start(sid),
-                    ));
-                    sched.add(Job::new(
-                        server.end.parse().expect("bad end syntax"),
-                        move || process_end(sid),
-                    ));
+        thread::spawn(move || {
+            let mut check_sched = JobScheduler::new();
+            let check_schedule = {
+                let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+                settings
+                    .get_str("premade-creator.check")
+                    .unwrap_or_else(|_| check::DEFAULT_SCHEDULE.to_string())
+            };
+            check_sched.add(Job::new(
+                check_schedule.parse().expect("bad check syntax"),
+                check::check_all,
+            ));
+
+            // A new scheduler only fires from its second tick, so only the jobs that changed are
+            // rebuilt.
+            let mut servers = all_jobs();
+            loop {
+                while let Ok(reschedule) = sched_rx.try_recv() {
+                    match reschedule {
+                        Reschedule::Server(server_id) => {
+                            let jobs = {
+                                let config =
+                                    CONFIG.read().expect("couldn't lock config for reading");
+                                config
+                                    .get(&server_id)
+                                    .and_then(|server| server_jobs(server_id, server))
+                            };
+                            match jobs {
+                                Some(jobs) => servers.insert(server_id, jobs),
+                                None => servers.remove(&server_id),
+                            };
+                        }
+                        Reschedule::All => servers = all_jobs(),
+                    }
                 }
-            }
 
-            while sched_rx.try_recv().is_err() {
-                sched.tick();
+                check_sched.tick();
+                for jobs in servers.values_mut() {
+                    jobs.tick();
+                }
                 let tick_size = {
                     let settings = SETTINGS.read().expect("couldn't lock settings for reading");
                     Duration::from_secs(
@@ -158,21 +293,78 @@ impl Module for PremadeCreator {
         });
 
         framework.group("Premade Creator", |g| {
-            g.desc("Commands to manipulate the Premade Creator module")
-                .required_permissions(Permissions::MANAGE_GUILD)
-                .cmd("pmconfig get", creator_command::GetCommand::default())
-                .cmd("pmconfig create", creator_command::CreateCommand::default())
-                .cmd("pmconfig set", creator_command::SetCommand::default())
-                .cmd(
-                    "pmconfig add roles",
-                    creator_command::AddRolesCommand::default(),
-                ).cmd(
-                    "pmconfig add game",
-                    creator_command::AddGameCommand::default(),
-                ).cmd("pmconfig commit", creator_command::CommitCommand::default())
-                .command("pmrehash", |c| c.owners_only(true).exec(rehash))
+            modules::group(
+                self.name(),
+                Permissions::MANAGE_GUILD,
+                g.desc("Commands to manipulate the Premade Creator module")
+                    .cmd("pmconfig get", creator_command::GetCommand::default())
+                    .cmd("pmconfig create", creator_command::CreateCommand::default())
+                    .cmd("pmconfig set", creator_command::SetCommand::default())
+                    .cmd(
+                        "pmconfig add roles",
+                        creator_command::AddRolesCommand::default(),
+                    ).cmd(
+                        "pmconfig add game",
+                        creator_command::AddGameCommand::default(),
+                    ).cmd("pmconfig commit", creator_command::CommitCommand::default())
+                    .cmd("pmconfig diff", creator_command::DiffCommand::default())
+                    .cmd("pmconfig check", check::CheckCommand::default())
+                    .cmd("pmconfig export", creator_command::ExportCommand::default())
+                    .cmd("pmconfig import", creator_command::ImportCommand::default())
+                    .cmd("pmconfig wizard", wizard::WizardCommand::default())
+                    .cmd("pmconfig history", history::HistoryCommand::default())
+                    .cmd("pmconfig rollback", history::RollbackCommand::default())
+                    .cmd(
+                        "pmconfig discard",
+                        creator_command::DiscardCommand::default(),
+                    )
+                    .cmd(
+                        "pmconfig template",
+                        creator_command::TemplateCommand::default(),
+                    )
+                    .cmd("pmskip", skip::SkipCommand::default())
+                    .command("pmrehash", |c| c.owners_only(true).exec(rehash)),
+            )
+        })
+        // Not in the group above: it's about every server, so disabling the module in one
+        // mustn't make owners ignored there.
+        .group("Premade Creator Status", |g| {
+            dispatch::index(
+                NAME,
+                Permissions::empty(),
+                g.desc("Health of the Premade Creator in every server")
+                    .owners_only(true)
+                    .cmd("status", check::StatusCommand::default()),
+            )
         })
     }
+
+    fn on_ready(&self, _ready: &Ready) {
+        // The servers that aren't available yet are checked when they are.
+        check::self_check_all();
+    }
+
+    fn on_shutdown(&self) {
+        if let Err(e) = save_config() {
+            warn!("Couldn't save config: {:?}", e);
+        }
+    }
+
+    fn on_guild_create(&self, guild: &Guild) {
+        check::self_check(guild.id);
+    }
+
+    fn on_guild_delete(&self, guild_id: GuildId) {
+        events::guild_deleted(guild_id);
+    }
+
+    fn on_channel_delete(&self, guild_id: GuildId, channel_id: ChannelId) {
+        events::channel_deleted(guild_id, channel_id);
+    }
+
+    fn on_role_delete(&self, guild_id: GuildId, role_id: RoleId) {
+        events::role_deleted(guild_id, role_id);
+    }
 }
 
 /// Convenience function to turn an Option<Vec<RoleId>> into a string mentioning the roles, if not
@@ -195,6 +387,20 @@ fn role_list_to_mentions(roles: &Option<Vec<RoleId>>) -> String {
 /// the proper roles proposing them a few games. Potential players need to react with the proper
 /// reactions.
 fn process_start(server_id: GuildId) {
+    // Until the poll is opened.
+    WITHOUT_POLL
+        .write()
+        .expect("couldn't lock WITHOUT_POLL for writing")
+        .insert(server_id);
+
+    if !modules::is_enabled(Some(server_id), NAME) {
+        info!("Module disabled in server {}, no poll", server_id);
+        return;
+    }
+    if blocklist::is_guild_blocked(server_id) {
+        info!("Server {} blocked, no poll", server_id);
+        return;
+    }
     info!(
         "Starting the premade creation process in server {}...",
         server_id
@@ -211,71 +417,107 @@ fn process_start(server_id: GuildId) {
     }
     let server = config.unwrap();
 
-    // Yeah I realize I could use the r#""# notation but this is way more readable imo.
-    let embed_description = vec![
-        "Today, these following games are available!".to_string(),
-        "React with the corresponding emoji to say you're available!".to_string(),
-        "I will send messages with all the participants in their respective games' channels.\n".to_string(),
-    ].join("\n");
-
-    let games = &server.games;
-    let mut reactions = Vec::with_capacity(games.len());
-    let mut embed_games = Vec::with_capacity(games.len());
-    for g in games.iter() {
-        reactions.push(g.emoji.clone());
-        embed_games.push(format!("{} -> `{}`", g.emoji, g.name));
+    if let Some(skip) = skip::find_skip(server_id, &Utc::now()) {
+        info!("Poll skipped in server {}: {}", server_id, skip.describe());
+        guild_log::post(
+            server_id,
+            Level::Info,
+            tr_with(
+                Some(server_id),
+                "log.poll_skipped",
+                &[("skip", skip.describe())],
+            ),
+        );
+        if skip::announces(server_id) {
+            let result = server.channel_id.send_message(|m| {
+                m.embed(|e| {
+                    e.color(Colour(server.templates.colour()))
+                        .title(tr(Some(server_id), "poll.skipped.title"))
+                        .description(tr_with(
+                            Some(server_id),
+                            "poll.skipped.description",
+                            &[("skip", skip.describe())],
+                        ))
+                })
+            });
+            if let Err(e) = result {
+                warn!("Couldn't send message to server {}: {:?}", server_id.0, e);
+                log_send_failure(server_id, server.channel_id);
+            }
+        }
+        return;
     }
 
-    let embed_games = embed_games
-        .into_iter()
-        .try_fold(FoldStrlenState::new(900), &fold_by_strlen)
-        .expect("error while creating games message");
-    let embed_games = embed_games
-        .extract()
-        .iter()
-        .map(|v| v.join("\n"))
-        .collect::<Vec<String>>();
-
-    if embed_games.is_empty() {
+    if server.games.is_empty() {
         return;
     }
 
-    let embed = CreateEmbed::default();
-    let embed = embed
-        .color(Colour::from_rgb(120, 17, 176))
-        .title("Pick your games!")
-        .description(&embed_description)
-        .field("Games", &embed_games[0], false)
-        .fields(embed_games[1..].iter().map(|g| ("Games (cont)", g, false)));
-
-    let message = CreateMessage::default();
-    let message = message.embed(|_| embed).reactions(reactions.into_iter());
-
-    let message = message.content(role_list_to_mentions(&server.role_ids));
-    match server.channel_id.send_message(|_| message) {
-        // Message successfully sent, keep the ID in memory
-        Ok(msg) => {
-            let mut state = STATE.write().expect("couldn't lock state for writing");
-            state.insert(server.channel_id, msg.id);
+    // One poll message per batch of games, each with the reactions of its games. Only the first
+    // one mentions the roles.
+    let mut message_ids = Vec::new();
+    for (i, games) in server.games.chunks(REACTIONS_PER_MESSAGE).enumerate() {
+        let reactions = games
+            .iter()
+            .map(|g| g.emoji.clone())
+            .collect::<Vec<ReactionType>>();
+
+        // The reactions go on the first embed, the others only carry the rest of the games list.
+        let mut embeds = poll_embeds(server_id, server, games).into_iter();
+        // There is always at least one embed.
+        let first = embeds.next().unwrap();
+        let message = CreateMessage::default();
+        let mut message = message.embed(|_| first).reactions(reactions);
+        if i == 0 {
+            message = message.content(role_list_to_mentions(&server.role_ids));
         }
-        // Message wasn't sent correctly. Forwarding error to user.
-        Err(e) => {
+
+        let result = server
+            .channel_id
+            .send_message(|_| message)
+            .map(|msg| message_ids.push(msg.id))
+            .and_then(|_| send_embeds(server.channel_id, embeds.collect()));
+        if let Err(e) = result {
             warn!("Couldn't send message to server {}: {:?}", server_id.0, e);
+            log_send_failure(server_id, server.channel_id);
+            break;
         }
-    };
+    }
+
+    // Keep the IDs of the messages that were sent in memory, even if some are missing.
+    if !message_ids.is_empty() {
+        guild_log::post(
+            server_id,
+            Level::Info,
+            tr_with(
+                Some(server_id),
+                "log.poll_opened",
+                &[
+                    ("channel", server.channel_id.mention()),
+                    ("count", server.games.len().to_string()),
+                ],
+            ),
+        );
+        let mut state = STATE.write().expect("couldn't lock state for writing");
+        state.insert(server.channel_id, message_ids);
+        WITHOUT_POLL
+            .write()
+            .expect("couldn't lock WITHOUT_POLL for writing")
+            .remove(&server_id);
+    }
 }
 
 /// Function called at the "end" event. Traverses all the list of servers to find out the messages
 /// it sent, and writes a message with all players for every particular game.
 fn process_end(server_id: GuildId) {
+    let without_poll = WITHOUT_POLL
+        .write()
+        .expect("couldn't lock WITHOUT_POLL for writing")
+        .remove(&server_id);
+    if !modules::is_enabled(Some(server_id), NAME) {
+        return;
+    }
     info!("Ending the premade creation process...");
 
-    let embed = CreateEmbed::default();
-    let embed = embed
-        .color(Colour::from_rgb(120, 17, 176))
-        .title("Today's players")
-        .description("The following players want to play:");
-
     let config = CONFIG.read().expect("couldn't lock config for reading");
     let config = config.get(&server_id);
     if config.is_none() {
@@ -288,81 +530,241 @@ fn process_end(server_id: GuildId) {
     let server = config.unwrap();
 
     let games = &server.games;
-    let message_id = {
+    let message_ids = {
         let state = STATE.read().expect("couldn't lock state for reading");
         match state.get(&server.channel_id) {
+            None if without_poll => {
+                info!("No poll to close in server {}", server_id);
+                return;
+            }
             None => {
                 warn!("Initial message not found for server {}!", server_id);
+                guild_log::post(
+                    server_id,
+                    Level::Warning,
+                    tr(Some(server_id), "log.poll_lost"),
+                );
                 return;
             }
-            Some(mid) => *mid,
+            Some(mids) => mids.clone(),
         }
     };
 
+    // Reactions can be on any of the poll messages (people don't always react where the game is
+    // listed), so every message is looked at for every game.
+    let messages = message_ids
+        .iter()
+        .filter_map(|mid| match server.channel_id.message(*mid) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                warn!("Couldn't get poll message {} in server {}: {:?}", mid, server_id, e);
+                guild_log::post(
+                    server_id,
+                    Level::Warning,
+                    tr_with(
+                        Some(server_id),
+                        "log.poll_message_missing",
+                        &[("message", mid.to_string())],
+                    ),
+                );
+                None
+            }
+        }).collect::<Vec<Message>>();
+
+    let mut played = 0;
     for g in games.iter() {
-        let mentions = server
-            .channel_id
-            .reaction_users(message_id, g.emoji.clone(), None, None)
-            .expect("couldn't get reactions");
-        let mentions = mentions.iter().filter(|user| !user.bot);
-        let mentions = mentions
-            .map(&User::mention)
-            .try_fold(FoldStrlenState::new(900), &fold_by_strlen)
-            .expect("error while making mentions");
-        let mut mentions = mentions
-            .extract()
+        let mut players: Vec<User> = Vec::new();
+        for message in messages.iter() {
+            let reacted = message
+                .reactions
+                .iter()
+                .any(|r| r.count > 0 && same_emoji(&r.reaction_type, &g.emoji));
+            if !reacted {
+                continue;
+            }
+            let users = match server
+                .channel_id
+                .reaction_users(message.id, g.emoji.clone(), None, None)
+            {
+                Ok(users) => users,
+                Err(e) => {
+                    warn!("Couldn't get reactions in server {}: {:?}", server_id, e);
+                    continue;
+                }
+            };
+            for user in users {
+                // Blocked users can react, but they don't get to play.
+                if !user.bot
+                    && !blocklist::is_user_blocked(user.id)
+                    && players.iter().all(|p| p.id != user.id)
+                {
+                    players.push(user);
+                }
+            }
+        }
+        let mentions = players
             .iter()
-            .map(|v| v.join(", "))
+            .map(&User::mention)
             .collect::<Vec<String>>();
 
         // If nobody answered for this particular game, skip
         if mentions.is_empty() {
             continue;
         }
+        played += 1;
 
-        let embed = embed.clone();
-        let embed = embed
-            .field(format!("{} {}", g.emoji, g.name), &mentions[0], false)
-            .fields(
-                mentions[1..]
-                    .iter()
-                    .map(|m| (format!("{} {} (cont)", g.emoji, g.name), m, false)),
-            );
-
-        let result = g.channel_id.send_message(|m| {
-            let message = m.embed(|_| embed);
-            message.content(role_list_to_mentions(&g.role_ids))
-        });
+        // The roles are only mentioned in the first message.
+        let mut embeds = result_embeds(server_id, server, g, &mentions).into_iter();
+        // There is always at least one embed.
+        let first = embeds.next().unwrap();
+        let result = g
+            .channel_id
+            .send_message(|m| {
+                let message = m.embed(|_| first);
+                message.content(role_list_to_mentions(&g.role_ids))
+            }).and_then(|_| send_embeds(g.channel_id, embeds.collect()));
         if let Err(err) = result {
             warn!(
                 "The message couldn't be sent to server {}: {:?}",
                 server_id, err
             );
+            log_send_failure(server_id, g.channel_id);
         }
     }
 
+    guild_log::post(
+        server_id,
+        Level::Info,
+        tr_with(
+            Some(server_id),
+            "log.poll_closed",
+            &[("count", played.to_string())],
+        ),
+    );
+
     let mut state = STATE.write().expect("couldn't lock state for writing");
     state.remove(&server.channel_id);
 }
 
-use std::fs::OpenOptions;
+/// Tells the admins of a server that a message couldn't be sent in a channel.
+fn log_send_failure(server_id: GuildId, channel_id: ChannelId) {
+    guild_log::post(
+        server_id,
+        Level::Error,
+        tr_with(
+            Some(server_id),
+            "log.send_failed",
+            &[("channel", channel_id.mention())],
+        ),
+    );
+}
 
-fn save_config() -> Result<(), String> {
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open("data/premade_creator.json");
-    let file = file.map_err(|e| e.to_string())?;
+/// Whether two emojis are the same. Custom emojis are compared by ID only, since their name in the
+/// config can differ from the one Discord gives.
+fn same_emoji(a: &ReactionType, b: &ReactionType) -> bool {
+    match (a, b) {
+        (ReactionType::Custom { id: a, .. }, ReactionType::Custom { id: b, .. }) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Builds the poll embeds of a server from its template, listing the given games.
+fn poll_embeds(server_id: GuildId, server: &Server, games: &[GameInfo]) -> Vec<CreateEmbed> {
+    let placeholders = [
+        ("date", Utc::now().format("%Y-%m-%d").to_string()),
+        ("end_time", end_time(server)),
+        ("count", server.games.len().to_string()),
+    ];
+    let language = i18n::language(Some(server_id));
+    let embed = server.templates.poll_embed(&language, &placeholders);
+
+    let games = games
+        .iter()
+        .map(|g| format!("{} -> `{}`", g.emoji, g.name))
+        .collect();
+
+    paginate_embed(
+        &embed,
+        vec![Section::new(i18n::get(&language, "poll.games"), games)],
+        &i18n::get(&language, "cont"),
+    )
+}
+
+/// Builds the result embeds of a game from the server's template, listing its players.
+fn result_embeds(
+    server_id: GuildId,
+    server: &Server,
+    game: &GameInfo,
+    players: &[String],
+) -> Vec<CreateEmbed> {
+    let now = Utc::now();
+    let placeholders = [
+        ("date", now.format("%Y-%m-%d").to_string()),
+        ("end_time", now.format("%H:%M UTC").to_string()),
+        ("game", game.name.clone()),
+        ("count", players.len().to_string()),
+        ("players", players.join(", ")),
+    ];
+    let language = i18n::language(Some(server_id));
+    let embed = server.templates.result_embed(&language, &placeholders);
+
+    paginate_embed(
+        &embed,
+        vec![Section::new(format!("{} {}", game.emoji, game.name), players.to_vec()).separator(", ")],
+        &i18n::get(&language, "cont"),
+    )
+}
+
+/// Next start event of a server and the end event following it, for display purposes.
+fn next_events(server_id: GuildId, server: &Server) -> String {
+    let start = server
+        .start
+        .parse::<cron::Schedule>()
+        .ok()
+        .and_then(|s| s.upcoming(Utc).next());
+    let end = start.and_then(|start| {
+        server
+            .end
+            .parse::<cron::Schedule>()
+            .ok()
+            .and_then(|s| s.after(&start).next())
+    });
+    let format = |time: Option<DateTime<Utc>>| {
+        time.map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| tr(Some(server_id), "none"))
+    };
+
+    tr_with(
+        Some(server_id),
+        "pm.next_events",
+        &[("start", format(start)), ("end", format(end))],
+    )
+}
+
+/// Time of the next end event of a server, for display purposes.
+fn end_time(server: &Server) -> String {
+    server
+        .end
+        .parse::<cron::Schedule>()
+        .ok()
+        .and_then(|s| s.upcoming(Utc).next())
+        .map(|t| t.format("%H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn save_config() -> Result<(), Error> {
+    let file = File::create("data/premade_creator.json")?;
 
     let config = CONFIG.read().expect("couldn't lock CONFIG for writing");
-    to_writer_pretty(file, &*config).map_err(|e| format!("{}", e))
+    to_writer_pretty(file, &*config)?;
+    Ok(())
 }
 
 /// Represents a game info.
 /// A game has a name that will represent it everywhere, an emoji used in reactions, a list of
 /// roles to be @ed when the game's message is sent, and a channel to send the message to.
 #[derive(Clone, Serialize, Deserialize)]
-struct GameInfo {
+pub struct GameInfo {
     name: String,
     emoji: ReactionType,
     role_ids: Option<Vec<RoleId>>,
@@ -374,10 +776,12 @@ struct GameInfo {
 /// start and end strings representing times at which the events will fire (cron syntax),
 /// and an optional list of roles to be @ed when the messages are sent.
 #[derive(Clone, Serialize, Deserialize, Default)]
-struct Server {
+pub struct Server {
     channel_id: ChannelId,
     start: String,
     end: String,
     role_ids: Option<Vec<RoleId>>,
     games: Vec<GameInfo>,
+    #[serde(default)]
+    templates: template::Templates,
 }
@@ -0,0 +1,413 @@
+//! Schedules in plain terms, turned into the cron expressions stored in the configuration.
+//!
+//! A plain schedule is `<days> at <HH:MM>`, like `weekdays at 19:00` or `sat,sun at 14:30`.
+//! Days are `every day` (or `daily`), `weekdays`, `weekends`, or a list of days like
+//! `mon, wed, fri` (full names work too). Times are in UTC.
+//! The end of a poll can also be given relative to its start, like `+3h`, `+45m` or `+1h30m`.
+//!
+//! Cron expressions made from plain schedules are shown back in plain terms.
+//!
+//! Schedules are also checked for sense: the polls must happen, be at least
+//! `premade-creator.min_interval` seconds apart (an hour by default), and each one must end
+//! before the next one starts.
+
+use chrono::prelude::*;
+use chrono::Duration;
+use cron;
+
+use serenity::model::prelude::*;
+
+use i18n::{tr, tr_with};
+use SETTINGS;
+
+/// Cron names of the days, in the order of the week.
+const DAYS: &[&str] = &["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Minimum interval between two polls if `premade-creator.min_interval` isn't set.
+const DEFAULT_MIN_INTERVAL: i64 = 3600;
+
+/// Number of upcoming polls checked.
+const CHECKED_POLLS: usize = 50;
+
+/// Number of upcoming polls shown.
+const SHOWN_POLLS: usize = 3;
+
+/// Reads days, as indexes in `DAYS`, sorted.
+fn read_days(text: &str) -> Option<Vec<usize>> {
+    let text = text.trim().to_lowercase();
+    match text.as_str() {
+        "every day" | "daily" => return Some((0..7).collect()),
+        "weekdays" => return Some((0..5).collect()),
+        "weekends" => return Some(vec![5, 6]),
+        _ => (),
+    }
+
+    let mut days = Vec::new();
+    for word in text.split(|c: char| c == ',' || c.is_whitespace()) {
+        if word.is_empty() {
+            continue;
+        }
+        // "mon", "monday"...: the first three letters are enough.
+        if word.chars().count() < 3 {
+            return None;
+        }
+        let prefix = word.chars().take(3).collect::<String>();
+        let day = DAYS
+            .iter()
+            .position(|d| d.to_lowercase() == prefix)?;
+        days.push(day);
+    }
+    if days.is_empty() {
+        return None;
+    }
+
+    days.sort();
+    days.dedup();
+    Some(days)
+}
+
+/// Day-of-week field of a cron expression.
+fn days_field(days: &[usize]) -> String {
+    match days {
+        [0, 1, 2, 3, 4, 5, 6] => "*".to_string(),
+        [0, 1, 2, 3, 4] => "Mon-Fri".to_string(),
+        _ => days.iter().map(|&d| DAYS[d]).collect::<Vec<&str>>().join(","),
+    }
+}
+
+/// Reads back a day-of-week field written by `days_field`.
+fn read_days_field(field: &str) -> Option<Vec<usize>> {
+    match field {
+        "*" => Some((0..7).collect()),
+        "Mon-Fri" => Some((0..5).collect()),
+        _ => field
+            .split(',')
+            .map(|d| DAYS.iter().position(|&n| n == d))
+            .collect(),
+    }
+}
+
+/// Days in plain terms.
+fn describe_days(days: &[usize]) -> String {
+    match days {
+        [0, 1, 2, 3, 4, 5, 6] => "every day".to_string(),
+        [0, 1, 2, 3, 4] => "weekdays".to_string(),
+        [5, 6] => "weekends".to_string(),
+        _ => days
+            .iter()
+            .map(|&d| DAYS[d].to_lowercase())
+            .collect::<Vec<String>>()
+            .join(","),
+    }
+}
+
+/// Turns days into the day-of-week field of a cron expression.
+pub fn parse_days(text: &str) -> Option<String> {
+    read_days(text).map(|d| days_field(&d))
+}
+
+/// Reads a `HH:MM` time.
+pub fn parse_time(text: &str) -> Option<(u32, u32)> {
+    let mut parts = text.trim().splitn(2, ':');
+    let hour = parts.next()?.parse::<u32>().ok()?;
+    let minute = parts.next()?.parse::<u32>().ok()?;
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+
+    Some((hour, minute))
+}
+
+/// Cron expression firing on the given days at the given time.
+pub fn to_cron(days: &str, (hour, minute): (u32, u32)) -> String {
+    format!("0 {} {} * * {}", minute, hour, days)
+}
+
+/// Reads a plain schedule.
+fn read_plain(text: &str) -> Option<(Vec<usize>, (u32, u32))> {
+    let mut parts = text.rsplitn(2, " at ");
+    let time = parse_time(parts.next()?)?;
+    let days = read_days(parts.next()?)?;
+
+    Some((days, time))
+}
+
+/// Reads back a cron expression made from a plain schedule.
+fn read_simple_cron(expr: &str) -> Option<(Vec<usize>, (u32, u32))> {
+    match expr.split_whitespace().collect::<Vec<&str>>()[..] {
+        ["0", minute, hour, "*", "*", days] => {
+            let time = parse_time(&format!("{}:{}", hour, minute))?;
+            Some((read_days_field(days)?, time))
+        }
+        _ => None,
+    }
+}
+
+/// Reads a duration like `+3h`, `+45m` or `+1h30m`, in minutes. Durations too long to count are
+/// refused.
+fn read_duration(text: &str) -> Option<u32> {
+    let mut rest = text.trim().trim_start_matches('+');
+    let mut minutes: u32 = 0;
+    if let Some(i) = rest.find('h') {
+        minutes = rest[..i].parse::<u32>().ok()?.checked_mul(60)?;
+        rest = &rest[i + 1..];
+    }
+    if !rest.is_empty() {
+        minutes = minutes.checked_add(rest.trim_end_matches('m').parse::<u32>().ok()?)?;
+    }
+
+    Some(minutes)
+}
+
+/// The schedule of a configuration in plain terms, if it was made from one.
+pub fn describe(expr: &str) -> Option<String> {
+    read_simple_cron(expr).map(|(days, (hour, minute))| {
+        format!("{} at {:02}:{:02}", describe_days(&days), hour, minute)
+    })
+}
+
+/// Turns the start and end of a configuration into cron expressions. Both can be cron
+/// expressions or plain schedules; the end can also be relative to the start.
+pub fn parse_events(
+    server_id: GuildId,
+    start: &str,
+    end: &str,
+) -> Result<(String, String), String> {
+    let invalid = |schedule: &str, error: String| {
+        tr_with(
+            Some(server_id),
+            "schedule.invalid",
+            &[("schedule", schedule.to_string()), ("error", error)],
+        )
+    };
+    let parse = |schedule: &str| match read_plain(schedule) {
+        Some((days, time)) => Ok(to_cron(&days_field(&days), time)),
+        None => schedule
+            .parse::<cron::Schedule>()
+            .map(|_| schedule.to_string())
+            .map_err(|e| invalid(schedule, e.to_string())),
+    };
+
+    let start = parse(start)?;
+    if !end.trim().starts_with('+') {
+        return Ok((start, parse(end)?));
+    }
+
+    let duration = read_duration(end)
+        .filter(|&d| d > 0 && d < 7 * MINUTES_PER_DAY)
+        .ok_or_else(|| {
+            tr_with(
+                Some(server_id),
+                "schedule.bad_duration",
+                &[("duration", end.to_string())],
+            )
+        })?;
+    let (days, (hour, minute)) = read_simple_cron(&start)
+        .ok_or_else(|| tr(Some(server_id), "schedule.relative_start"))?;
+
+    // The end can fall on the next days.
+    let end = hour * 60 + minute + duration;
+    let shift = (end / MINUTES_PER_DAY) as usize;
+    let end = end % MINUTES_PER_DAY;
+    let mut days = days.iter().map(|d| (d + shift) % 7).collect::<Vec<usize>>();
+    days.sort();
+    days.dedup();
+
+    Ok((start, to_cron(&days_field(&days), (end / 60, end % 60))))
+}
+
+fn min_interval() -> Duration {
+    let settings = SETTINGS.read().expect("couldn't lock settings for reading");
+    Duration::seconds(
+        settings
+            .get::<i64>("premade-creator.min_interval")
+            .unwrap_or(DEFAULT_MIN_INTERVAL),
+    )
+}
+
+/// Start and end of a poll.
+pub type Poll = (DateTime<Utc>, DateTime<Utc>);
+
+/// Checks that the events of a configuration make sense, and returns the next polls.
+pub fn check_events(server_id: GuildId, start: &str, end: &str) -> Result<Vec<Poll>, String> {
+    let format = |time: &DateTime<Utc>| time.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let parse = |schedule: &str| {
+        schedule.parse::<cron::Schedule>().map_err(|e| {
+            tr_with(
+                Some(server_id),
+                "schedule.invalid",
+                &[("schedule", schedule.to_string()), ("error", e.to_string())],
+            )
+        })
+    };
+    let start = parse(start)?;
+    let end = parse(end)?;
+
+    // One more start than checked, to know when the last checked poll must end.
+    let starts = start
+        .upcoming(Utc)
+        .take(CHECKED_POLLS + 1)
+        .collect::<Vec<DateTime<Utc>>>();
+    if starts.is_empty() {
+        return Err(tr(Some(server_id), "schedule.never"));
+    }
+
+    let min_interval = min_interval();
+    let mut polls = Vec::new();
+    for (i, poll_start) in starts.iter().enumerate().take(CHECKED_POLLS) {
+        let next_start = starts.get(i + 1);
+        if let Some(next_start) = next_start {
+            if *next_start - *poll_start < min_interval {
+                return Err(tr_with(
+                    Some(server_id),
+                    "schedule.too_often",
+                    &[
+                        ("start", format(poll_start)),
+                        ("next", format(next_start)),
+                        ("interval", (min_interval.num_minutes()).to_string()),
+                    ],
+                ));
+            }
+        }
+
+        let poll_end = end
+            .after(poll_start)
+            .next()
+            .filter(|e| next_start.map(|n| e < n).unwrap_or(true))
+            .ok_or_else(|| {
+                tr_with(
+                    Some(server_id),
+                    "schedule.no_end",
+                    &[("start", format(poll_start))],
+                )
+            })?;
+        polls.push((*poll_start, poll_end));
+    }
+
+    polls.truncate(SHOWN_POLLS);
+    Ok(polls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER: GuildId = GuildId(0);
+
+    #[test]
+    fn days_are_read_by_name_and_group() {
+        assert_eq!(read_days("every day"), Some((0..7).collect()));
+        assert_eq!(read_days("Weekdays"), Some((0..5).collect()));
+        assert_eq!(read_days("weekends"), Some(vec![5, 6]));
+        assert_eq!(read_days("fri, Mon,wednesday"), Some(vec![0, 2, 4]));
+        assert_eq!(read_days("sun sun"), Some(vec![6]));
+    }
+
+    #[test]
+    fn unknown_days_are_refused() {
+        assert_eq!(read_days(""), None);
+        assert_eq!(read_days("mo"), None);
+        assert_eq!(read_days("mon, someday"), None);
+        // Multi-byte characters within the first three bytes.
+        assert_eq!(read_days("mié"), None);
+        assert_eq!(read_days("sáb"), None);
+        assert_eq!(read_days("日曜日"), None);
+        assert_eq!(read_plain("mié at 19:00"), None);
+    }
+
+    #[test]
+    fn times_are_checked() {
+        assert_eq!(parse_time("19:05"), Some((19, 5)));
+        assert_eq!(parse_time(" 0:00 "), Some((0, 0)));
+        assert_eq!(parse_time("23:59"), Some((23, 59)));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("12:60"), None);
+        assert_eq!(parse_time("12"), None);
+        assert_eq!(parse_time("１９:００"), None);
+    }
+
+    #[test]
+    fn plain_schedules_become_cron() {
+        assert_eq!(
+            parse_events(SERVER, "weekdays at 19:00", "weekdays at 21:30"),
+            Ok((
+                "0 0 19 * * Mon-Fri".to_string(),
+                "0 30 21 * * Mon-Fri".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_events(SERVER, "0 0 12 * * Sat", "0 0 14 * * Sat"),
+            Ok(("0 0 12 * * Sat".to_string(), "0 0 14 * * Sat".to_string()))
+        );
+        assert_eq!(describe("0 0 19 * * Mon-Fri"), Some("weekdays at 19:00".to_string()));
+        assert_eq!(describe("0 0 12 1 * *"), None);
+    }
+
+    #[test]
+    fn relative_ends_can_span_midnight() {
+        assert_eq!(
+            parse_events(SERVER, "daily at 22:00", "+3h"),
+            Ok(("0 0 22 * * *".to_string(), "0 0 1 * * *".to_string()))
+        );
+        assert_eq!(
+            parse_events(SERVER, "fri,sun at 23:15", "+1h30m"),
+            Ok(("0 15 23 * * Fri,Sun".to_string(), "0 45 0 * * Mon,Sat".to_string()))
+        );
+        assert_eq!(
+            parse_events(SERVER, "mon at 12:00", "+45m"),
+            Ok(("0 0 12 * * Mon".to_string(), "0 45 12 * * Mon".to_string()))
+        );
+    }
+
+    #[test]
+    fn durations_are_read_in_minutes() {
+        assert_eq!(read_duration("+3h"), Some(180));
+        assert_eq!(read_duration("+1h30m"), Some(90));
+        assert_eq!(read_duration(" +45m "), Some(45));
+        assert_eq!(read_duration("+71582788h15m"), Some(71_582_788 * 60 + 15));
+        assert_eq!(read_duration("+71582789h"), None);
+        assert_eq!(read_duration("+4294967296m"), None);
+        assert_eq!(read_duration("+71582788h59999m"), None);
+        assert_eq!(read_duration("+h"), None);
+    }
+
+    #[test]
+    fn bad_events_are_refused() {
+        assert!(parse_events(SERVER, "not a schedule", "+3h").is_err());
+        assert!(parse_events(SERVER, "daily at 19:00", "+0m").is_err());
+        assert!(parse_events(SERVER, "daily at 19:00", "+7d").is_err());
+        assert!(parse_events(SERVER, "daily at 19:00", "+3é").is_err());
+        assert!(parse_events(SERVER, "daily at 19:00", "+71582789h").is_err());
+        assert!(parse_events(SERVER, "daily at 19:00", "+71582788h59999m").is_err());
+        // Relative ends need a plain start.
+        assert!(parse_events(SERVER, "0 0 19 1 * *", "+3h").is_err());
+    }
+
+    #[test]
+    fn polls_are_checked() {
+        let polls = check_events(SERVER, "0 0 19 * * *", "0 0 21 * * *").unwrap();
+        assert_eq!(polls.len(), SHOWN_POLLS);
+        for (start, end) in polls {
+            assert_eq!(end - start, Duration::hours(2));
+        }
+
+        // Ending after midnight.
+        let polls = check_events(SERVER, "0 0 22 * * *", "0 0 1 * * *").unwrap();
+        for (start, end) in polls {
+            assert_eq!(end - start, Duration::hours(3));
+        }
+    }
+
+    #[test]
+    fn bad_polls_are_refused() {
+        assert!(check_events(SERVER, "nonsense", "0 0 21 * * *").is_err());
+        // Too often.
+        assert!(check_events(SERVER, "0 * * * * *", "30 * * * * *").is_err());
+        // The next poll starts before this one ends.
+        assert!(check_events(SERVER, "0 0 19 * * *", "0 0 18 * * Mon").is_err());
+        // Never happens.
+        assert!(check_events(SERVER, "0 0 19 * * * 2001", "0 0 21 * * *").is_err());
+    }
+}
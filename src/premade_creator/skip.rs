@@ -0,0 +1,510 @@
+//! Skipped occurrences and blackout dates.
+//!
+//! Skips are kept apart from the server configuration, in `data/premade_creator_skips.json`, so
+//! that committing a draft doesn't wipe them. A skip covers a span of time (UTC, like the
+//! schedules): any start event firing inside it won't post a poll.
+//!
+//! Blackout calendars can be imported from `.ics` files placed by the host in `data/calendars`.
+//! Their times must be in UTC or floating: times in a named zone (`TZID`) are refused.
+
+use chrono::prelude::*;
+use chrono::Duration;
+use cron;
+
+use serenity::builder::*;
+use serenity::framework::standard::*;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::*;
+
+use serde_json::{from_reader, to_writer_pretty};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use error::Error;
+use i18n::{tr, tr_with};
+use utils::*;
+
+use super::template;
+use super::CONFIG;
+
+lazy_static! {
+    static ref BLACKOUTS: RwLock<HashMap<GuildId, Blackout>> = {
+        RwLock::new(initialize_blackouts())
+    };
+}
+
+/// A span of time during which no poll is posted.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Skip {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    reason: Option<String>,
+}
+
+impl Skip {
+    fn covers(&self, time: &DateTime<Utc>) -> bool {
+        self.from <= *time && *time <= self.to
+    }
+
+    /// Human readable form of the skip, used in lists and announcements.
+    pub fn describe(&self) -> String {
+        let span = if self.from.date() == self.to.date() && self.from.time() != self.to.time() {
+            format!(
+                "{} → {}",
+                self.from.format("%Y-%m-%d %H:%M"),
+                self.to.format("%H:%M")
+            )
+        } else if self.from.date() == self.to.date() {
+            format!("{}", self.from.format("%Y-%m-%d"))
+        } else {
+            format!(
+                "{} → {}",
+                self.from.format("%Y-%m-%d"),
+                self.to.format("%Y-%m-%d")
+            )
+        };
+
+        match self.reason {
+            Some(ref reason) => format!("{} UTC ({})", span, reason),
+            None => format!("{} UTC", span),
+        }
+    }
+}
+
+/// Blackout data for a server: the skips themselves, and whether skipped polls should be
+/// announced in the server's poll channel.
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct Blackout {
+    announce: bool,
+    skips: Vec<Skip>,
+}
+
+fn initialize_blackouts() -> HashMap<GuildId, Blackout> {
+    let file = File::open("data/premade_creator_skips.json");
+    if file.is_err() {
+        return HashMap::new();
+    }
+    let file = file.unwrap();
+    from_reader(file).unwrap_or_else(|e| {
+        warn!("couldn't deserialize premade_creator skips: {:?}", e);
+        HashMap::new()
+    })
+}
+
+fn save_blackouts() -> Result<(), Error> {
+    let file = File::create("data/premade_creator_skips.json")?;
+
+    let blackouts = BLACKOUTS
+        .read()
+        .expect("couldn't lock BLACKOUTS for reading");
+    to_writer_pretty(file, &*blackouts)?;
+    Ok(())
+}
+
+/// Returns the skip covering `time` for this server, if any.
+pub fn find_skip(server_id: GuildId, time: &DateTime<Utc>) -> Option<Skip> {
+    let blackouts = BLACKOUTS
+        .read()
+        .expect("couldn't lock BLACKOUTS for reading");
+    blackouts
+        .get(&server_id)
+        .and_then(|b| b.skips.iter().find(|s| s.covers(time)).cloned())
+}
+
+/// Whether skipped polls should be announced for this server.
+pub fn announces(server_id: GuildId) -> bool {
+    let blackouts = BLACKOUTS
+        .read()
+        .expect("couldn't lock BLACKOUTS for reading");
+    blackouts
+        .get(&server_id)
+        .map(|b| b.announce)
+        .unwrap_or_default()
+}
+
+/// Lists the skips that haven't ended yet, sorted by start.
+fn upcoming_skips(server_id: GuildId) -> Vec<Skip> {
+    let now = Utc::now();
+    let blackouts = BLACKOUTS
+        .read()
+        .expect("couldn't lock BLACKOUTS for reading");
+    let mut skips: Vec<Skip> = blackouts
+        .get(&server_id)
+        .map(|b| b.skips.iter().filter(|s| s.to >= now).cloned().collect())
+        .unwrap_or_default();
+    skips.sort_by_key(|s| s.from);
+    skips
+}
+
+/// Adds skips to a server, dropping the ones that are already over on the way.
+fn add_skips(server_id: GuildId, mut skips: Vec<Skip>) -> Result<(), Error> {
+    {
+        let now = Utc::now();
+        let mut blackouts = BLACKOUTS
+            .write()
+            .expect("couldn't lock BLACKOUTS for writing");
+        let blackout = blackouts.entry(server_id).or_default();
+        blackout.skips.retain(|s| s.to >= now);
+        blackout.skips.append(&mut skips);
+    }
+
+    save_blackouts()
+}
+
+/// Parses a `YYYY-MM-DD` or `YYYY-MM-DD..YYYY-MM-DD` range into a skip covering whole days.
+fn parse_range(server_id: GuildId, range: &str, reason: Option<String>) -> Result<Skip, String> {
+    let mut bounds = range.splitn(2, "..");
+    // splitn always yields at least one element.
+    let from = bounds.next().unwrap();
+    let to = bounds.next().unwrap_or(from);
+
+    let parse = |date: &str| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            tr_with(
+                Some(server_id),
+                "skip.bad_date",
+                &[("date", date.to_string())],
+            )
+        })
+    };
+    let from = parse(from)?;
+    let to = parse(to)?;
+
+    if to < from {
+        return Err(tr(Some(server_id), "skip.bad_range"));
+    }
+
+    Ok(Skip {
+        from: Utc.from_utc_date(&from).and_hms(0, 0, 0),
+        to: Utc.from_utc_date(&to).and_hms(23, 59, 59),
+        reason,
+    })
+}
+
+/// Computes the skip for the next poll of the server that isn't already skipped. The skip spans
+/// from the start event to the following end event.
+fn next_occurrence(server_id: GuildId, reason: Option<String>) -> Result<Skip, String> {
+    let (start, end): (cron::Schedule, cron::Schedule) = {
+        let config = CONFIG.read().expect("couldn't lock CONFIG for reading");
+        let server = config
+            .get(&server_id)
+            .ok_or_else(|| tr(Some(server_id), "pm.get.not_found"))?;
+        (
+            server.start.parse().map_err(|e| format!("{}", e))?,
+            server.end.parse().map_err(|e| format!("{}", e))?,
+        )
+    };
+
+    let from = start
+        .upcoming(Utc)
+        .take(1000)
+        .find(|t| find_skip(server_id, t).is_none())
+        .ok_or_else(|| tr(Some(server_id), "skip.no_poll"))?;
+    let to = end
+        .after(&from)
+        .next()
+        .ok_or_else(|| tr(Some(server_id), "skip.no_poll"))?;
+
+    Ok(Skip { from, to, reason })
+}
+
+/// The parts of an iCalendar event we care about.
+#[derive(Default)]
+struct IcsEvent {
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    summary: Option<String>,
+}
+
+/// Reads the events of an iCalendar file and turns them into skips. Only `DTSTART`, `DTEND` and
+/// `SUMMARY` are read; all-day events end the day before their `DTEND`, as per the RFC.
+fn parse_ics<R: BufRead>(reader: R) -> Result<Vec<Skip>, String> {
+    // Unfold continuation lines first (lines starting with a space or a tab).
+    let mut lines: Vec<String> = Vec::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim_end_matches('\r').to_string();
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        lines.push(line);
+    }
+
+    let mut skips = Vec::new();
+    let mut event: Option<IcsEvent> = None;
+    for line in lines {
+        if line == "BEGIN:VEVENT" {
+            event = Some(IcsEvent::default());
+            continue;
+        }
+        if line == "END:VEVENT" {
+            match event.take() {
+                Some(IcsEvent {
+                    start: Some(from),
+                    end,
+                    summary,
+                }) => skips.push(Skip {
+                    from,
+                    to: end.unwrap_or_else(|| from + Duration::days(1)) - Duration::seconds(1),
+                    reason: summary,
+                }),
+                _ => return Err("an event has no DTSTART".to_string()),
+            }
+            continue;
+        }
+
+        let event = match event {
+            Some(ref mut e) => e,
+            None => continue,
+        };
+        let mut parts = line.splitn(2, ':');
+        // splitn always yields at least one element.
+        let name = parts.next().unwrap();
+        let value = match parts.next() {
+            Some(v) => v,
+            None => continue,
+        };
+        // Drop the parameters (eg. `DTSTART;VALUE=DATE`), but refuse the time zones: they'd be
+        // read as UTC, shifting the events.
+        let mut parameters = name.split(';');
+        let name = parameters.next().unwrap();
+        let zoned = parameters.any(|p| p.starts_with("TZID="));
+        match name {
+            "DTSTART" | "DTEND" if zoned => {
+                return Err(format!(
+                    "`{}` has a time zone, please export the calendar in UTC",
+                    line
+                ))
+            }
+            "DTSTART" => event.start = Some(parse_ics_time(value)?),
+            "DTEND" => event.end = Some(parse_ics_time(value)?),
+            "SUMMARY" => event.summary = Some(value.replace("\\,", ",").replace("\\;", ";")),
+            _ => (),
+        }
+    }
+
+    Ok(skips)
+}
+
+/// Parses an iCalendar `DATE` or `DATE-TIME` value. Floating times are considered UTC.
+fn parse_ics_time(value: &str) -> Result<DateTime<Utc>, String> {
+    let value = value.trim_end_matches('Z');
+    if let Ok(time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Ok(Utc.from_utc_datetime(&time));
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .map(|d| Utc.from_utc_date(&d).and_hms(0, 0, 0))
+        .map_err(|e| format!("invalid calendar date `{}`: {}", value, e))
+}
+
+/// Lists the upcoming skips of a server, one per line.
+pub fn describe_skips(server_id: GuildId) -> Vec<String> {
+    let skips = upcoming_skips(server_id);
+    if skips.is_empty() {
+        return vec![tr(Some(server_id), "none")];
+    }
+
+    skips.iter().map(Skip::describe).collect()
+}
+
+/// Embeds listing the upcoming skips of a server.
+fn display_skips(server_id: GuildId, title: String) -> Vec<CreateEmbed> {
+    let base = CreateEmbed::default()
+        .title(title)
+        .color(Colour(template::DEFAULT_COLOUR));
+    let sections = vec![
+        Section::new(
+            tr(Some(server_id), "pm.display.skipped"),
+            describe_skips(server_id),
+        ),
+        Section::new(
+            tr(Some(server_id), "skip.announced"),
+            vec![tr(
+                Some(server_id),
+                if announces(server_id) { "yes" } else { "no" },
+            )],
+        ),
+    ];
+
+    paginate_embed(&base, sections, &tr(Some(server_id), "cont"))
+}
+
+#[derive(Default)]
+pub struct SkipCommand;
+
+/// Skips polls, either the next one or those inside a range of dates.
+impl Command for SkipCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some("Skips polls. `next` skips the next poll, a date or a range of dates (UTC) skips every poll inside it. `import` reads an .ics file put in data/calendars by the host. `announce` sets whether skipped polls are announced in the poll channel.".to_string());
+        options.usage = Some(
+            "(next|<YYYY-MM-DD>[..<YYYY-MM-DD>]) [reason] | import <file.ics> | announce (on|off) | list | clear"
+                .to_string(),
+        );
+        options.help_available = true;
+        options.min_args = Some(1);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, args: Args) -> Result<(), CommandError> {
+        let mut args = args;
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+
+        let subcommand = args.single::<String>()?;
+        let title = match subcommand.as_str() {
+            "list" => "skip.list.title",
+            "clear" => {
+                {
+                    let mut blackouts = BLACKOUTS
+                        .write()
+                        .expect("couldn't lock BLACKOUTS for writing");
+                    blackouts.entry(server_id).or_default().skips.clear();
+                }
+                save_blackouts()?;
+                "skip.clear.title"
+            }
+            "announce" => {
+                let announce = match args.single::<String>()?.as_str() {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(tr(Some(server_id), "skip.announce.on_off").into()),
+                };
+                {
+                    let mut blackouts = BLACKOUTS
+                        .write()
+                        .expect("couldn't lock BLACKOUTS for writing");
+                    blackouts.entry(server_id).or_default().announce = announce;
+                }
+                save_blackouts()?;
+                "skip.announce.title"
+            }
+            "import" => {
+                let name = args.single_quoted::<String>()?;
+                if name.contains('/') || name.contains('\\') || name.starts_with('.') {
+                    return Err(tr(Some(server_id), "skip.import.path").into());
+                }
+                let skips = File::open(format!("data/calendars/{}", name))
+                    .map_err(|e| e.to_string())
+                    .and_then(|file| parse_ics(BufReader::new(file)))
+                    .map_err(|e| {
+                        tr_with(Some(server_id), "skip.import.error", &[("error", e)])
+                    })?;
+                add_skips(server_id, skips)?;
+                "skip.import.title"
+            }
+            "next" => {
+                let reason = Some(args.rest().to_string()).filter(|r| !r.is_empty());
+                add_skips(server_id, vec![next_occurrence(server_id, reason)?])?;
+                "skip.next.title"
+            }
+            range => {
+                let reason = Some(args.rest().to_string()).filter(|r| !r.is_empty());
+                add_skips(server_id, vec![parse_range(server_id, range, reason)?])?;
+                "skip.dates.title"
+            }
+        };
+
+        send_embeds(
+            msg.channel_id,
+            display_skips(server_id, tr(Some(server_id), title)),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(calendar: &str) -> Result<Vec<Skip>, String> {
+        parse_ics(calendar.as_bytes())
+    }
+
+    #[test]
+    fn all_day_events_end_the_day_before() {
+        let skips = parse(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20241224\r\n\
+             DTEND;VALUE=DATE:20241226\r\n\
+             SUMMARY:Noël\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        ).unwrap();
+        assert_eq!(skips.len(), 1);
+        assert_eq!(skips[0].from, Utc.ymd(2024, 12, 24).and_hms(0, 0, 0));
+        assert_eq!(skips[0].to, Utc.ymd(2024, 12, 25).and_hms(23, 59, 59));
+        assert_eq!(skips[0].reason, Some("Noël".to_string()));
+    }
+
+    #[test]
+    fn events_without_end_last_a_day() {
+        let skips = parse(
+            "BEGIN:VEVENT\n\
+             DTSTART:20240714\n\
+             END:VEVENT\n",
+        ).unwrap();
+        assert_eq!(skips[0].to, Utc.ymd(2024, 7, 14).and_hms(23, 59, 59));
+        assert_eq!(skips[0].reason, None);
+    }
+
+    #[test]
+    fn timed_events_can_span_midnight() {
+        let skips = parse(
+            "BEGIN:VEVENT\n\
+             DTSTART:20241231T220000Z\n\
+             DTEND:20250101T020000Z\n\
+             SUMMARY:Réveillon\\, fête\n\
+             END:VEVENT\n",
+        ).unwrap();
+        assert_eq!(skips[0].from, Utc.ymd(2024, 12, 31).and_hms(22, 0, 0));
+        assert_eq!(skips[0].to, Utc.ymd(2025, 1, 1).and_hms(1, 59, 59));
+        assert_eq!(skips[0].reason, Some("Réveillon, fête".to_string()));
+        assert!(skips[0].covers(&Utc.ymd(2025, 1, 1).and_hms(0, 30, 0)));
+        assert!(!skips[0].covers(&Utc.ymd(2025, 1, 1).and_hms(2, 0, 0)));
+    }
+
+    #[test]
+    fn folded_lines_are_unfolded() {
+        let skips = parse(
+            "BEGIN:VEVENT\r\n\
+             DTSTART:20240501\r\n\
+             SUMMARY:Fête du\r\n \
+             travail\r\n\
+             END:VEVENT\r\n",
+        ).unwrap();
+        assert_eq!(skips[0].reason, Some("Fête dutravail".to_string()));
+    }
+
+    #[test]
+    fn broken_events_are_refused() {
+        assert!(parse("BEGIN:VEVENT\nSUMMARY:Rien\nEND:VEVENT\n").is_err());
+        assert!(parse("BEGIN:VEVENT\nDTSTART:2024-05-01\nEND:VEVENT\n").is_err());
+        assert!(parse("BEGIN:VEVENT\nDTSTART:２０２４０５０１\nEND:VEVENT\n").is_err());
+        assert!(parse(
+            "BEGIN:VEVENT\n\
+             DTSTART;TZID=Europe/Paris:20240501T100000\n\
+             END:VEVENT\n"
+        ).is_err());
+        assert!(parse(
+            "BEGIN:VEVENT\n\
+             DTSTART:20240501T080000Z\n\
+             DTEND;TZID=America/New_York:20240501T100000\n\
+             END:VEVENT\n"
+        ).is_err());
+        // Lines outside events are ignored.
+        assert_eq!(parse("DTSTART:nonsense\n").unwrap().len(), 0);
+        assert_eq!(parse("").unwrap().len(), 0);
+    }
+}
@@ -0,0 +1,213 @@
+//! Message templates for the poll and result messages.
+//!
+//! Every part of a template is optional: when it isn't set, the built-in text is used, in the
+//! language of the server. Texts can contain placeholders, written `{name}`, that get replaced
+//! when the message is sent:
+//! * `{date}`: today's date (UTC);
+//! * `{end_time}`: the time at which the poll closes (UTC);
+//! * `{count}`: the number of games in the poll, or of players for a result;
+//! * `{game}`: the name of the game (results only);
+//! * `{players}`: the players who reacted (results only).
+//!
+//! Unknown placeholders are left as-is.
+
+use serenity::builder::CreateEmbed;
+use serenity::utils::Colour;
+
+use i18n;
+use utils::fill_placeholders;
+
+/// Colour of every embed sent by the module, unless a template says otherwise.
+pub const DEFAULT_COLOUR: u32 = 0x78_11_B0;
+
+/// Highest colour Discord accepts in an embed.
+const MAX_COLOUR: u32 = 0xFF_FF_FF;
+
+/// A customizable embed. `None` means the built-in value.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Template {
+    title: Option<String>,
+    description: Option<String>,
+    footer: Option<String>,
+    colour: Option<u32>,
+    thumbnail: Option<String>,
+}
+
+/// The templates of a server, one for the poll and one for the results.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct Templates {
+    #[serde(default)]
+    pub poll: Template,
+    #[serde(default)]
+    pub result: Template,
+}
+
+impl Templates {
+    /// Colour of the server's embeds, taken from the poll template.
+    pub fn colour(&self) -> u32 {
+        self.poll.colour.unwrap_or(DEFAULT_COLOUR)
+    }
+
+    /// Embed used for the poll message.
+    pub fn poll_embed(&self, language: &str, placeholders: &[(&str, String)]) -> CreateEmbed {
+        self.poll.embed(
+            &i18n::get(language, "poll.title"),
+            &i18n::get(language, "poll.description"),
+            placeholders,
+        )
+    }
+
+    /// Embed used for a game's result message.
+    pub fn result_embed(&self, language: &str, placeholders: &[(&str, String)]) -> CreateEmbed {
+        self.result.embed(
+            &i18n::get(language, "result.title"),
+            &i18n::get(language, "result.description"),
+            placeholders,
+        )
+    }
+}
+
+impl Template {
+    fn embed(
+        &self,
+        title: &str,
+        description: &str,
+        placeholders: &[(&str, String)],
+    ) -> CreateEmbed {
+        let title = fill_placeholders(
+            self.title.as_ref().map_or(title, String::as_str),
+            placeholders,
+        );
+        let description = fill_placeholders(
+            self.description
+                .as_ref()
+                .map_or(description, String::as_str),
+            placeholders,
+        );
+
+        let mut embed = CreateEmbed::default()
+            .color(Colour(self.colour.unwrap_or(DEFAULT_COLOUR)))
+            .title(title)
+            .description(description);
+        if let Some(ref footer) = self.footer {
+            let footer = fill_placeholders(footer, placeholders);
+            embed = embed.footer(|f| f.text(footer));
+        }
+        if let Some(ref thumbnail) = self.thumbnail {
+            embed = embed.thumbnail(thumbnail);
+        }
+
+        embed
+    }
+
+    /// Sets a part of the template. Giving no value resets it to the built-in one.
+    /// Errors are message keys, `{part}` and `{value}` being the placeholders.
+    pub fn set(&mut self, part: &str, value: Option<String>) -> Result<(), &'static str> {
+        match part {
+            "title" => self.title = value,
+            "description" => self.description = value,
+            "footer" => self.footer = value,
+            "thumbnail" => {
+                if let Some(ref url) = value {
+                    if !is_web_url(url) {
+                        return Err("template.bad_url");
+                    }
+                }
+                self.thumbnail = value;
+            }
+            "colour" | "color" => {
+                self.colour = match value {
+                    None => None,
+                    Some(v) => Some(
+                        u32::from_str_radix(v.trim_start_matches('#'), 16)
+                            .ok()
+                            .filter(|&c| c <= MAX_COLOUR)
+                            .ok_or("template.bad_colour")?,
+                    ),
+                }
+            }
+            _ => return Err("template.bad_part"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether a text is an http(s) URL, the only ones Discord shows in embeds.
+fn is_web_url(text: &str) -> bool {
+    let rest = match text.strip_prefix("https://").or_else(|| text.strip_prefix("http://")) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let host = rest.split(&['/', '?', '#'][..]).next().unwrap_or("");
+    !host.is_empty() && !text.chars().any(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parts_are_set_and_reset() {
+        let mut template = Template::default();
+        assert_eq!(template.set("title", Some("Ce soir : {count} jeux".to_string())), Ok(()));
+        assert_eq!(template.title, Some("Ce soir : {count} jeux".to_string()));
+        assert_eq!(template.set("title", None), Ok(()));
+        assert_eq!(template.title, None);
+        assert_eq!(template.set("subtitle", Some("x".to_string())), Err("template.bad_part"));
+    }
+
+    #[test]
+    fn colours_are_hexadecimal_and_in_range() {
+        let mut template = Template::default();
+        assert_eq!(template.set("colour", Some("#7811B0".to_string())), Ok(()));
+        assert_eq!(template.colour, Some(0x78_11_B0));
+        assert_eq!(template.set("color", Some("FFFFFF".to_string())), Ok(()));
+        assert_eq!(template.colour, Some(MAX_COLOUR));
+        assert_eq!(template.set("colour", Some("000000".to_string())), Ok(()));
+        assert_eq!(template.colour, Some(0));
+
+        for bad in &["1000000", "FFFFFFFF", "violet", "#", "ééé"] {
+            assert_eq!(
+                template.set("colour", Some(bad.to_string())),
+                Err("template.bad_colour")
+            );
+        }
+        // Refused values don't change the template.
+        assert_eq!(template.colour, Some(0));
+    }
+
+    #[test]
+    fn thumbnails_are_web_urls() {
+        let mut template = Template::default();
+        for good in &[
+            "https://example.com/dice.png",
+            "http://example.com",
+            "https://exemple.fr/dé.png?taille=2",
+        ] {
+            assert_eq!(template.set("thumbnail", Some(good.to_string())), Ok(()));
+            assert_eq!(template.thumbnail, Some(good.to_string()));
+        }
+
+        for bad in &[
+            "example.com/dice.png",
+            "ftp://example.com/dice.png",
+            "https://",
+            "https:///dice.png",
+            "https://example.com/my dice.png",
+            "javascript:alert(1)",
+        ] {
+            assert_eq!(
+                template.set("thumbnail", Some(bad.to_string())),
+                Err("template.bad_url")
+            );
+        }
+        assert_eq!(
+            template.thumbnail,
+            Some("https://exemple.fr/dé.png?taille=2".to_string())
+        );
+
+        assert_eq!(template.set("thumbnail", None), Ok(()));
+        assert_eq!(template.thumbnail, None);
+    }
+}
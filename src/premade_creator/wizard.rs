@@ -0,0 +1,252 @@
+//! A step-by-step setup of the polls, for admins who'd rather not write cron expressions.
+//!
+//! `pmconfig wizard` asks for the channel, the days, the start and end times, the roles and the
+//! games, one question at a time, and checks every answer. At the end, the configuration is in
+//! the admin's draft, ready to be committed. `cancel` stops it at any time.
+
+use serenity::framework::standard::*;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use serenity::utils::parse_emoji;
+
+use std::sync::Arc;
+
+use conversation::{self, Conversation};
+use i18n::{tr, tr_with};
+use paginator::send_paginated;
+
+use super::creator_command::{display_server, other_editors, write_drafts, Draft};
+use super::schedule;
+use super::{GameInfo, Server};
+
+#[derive(Default)]
+pub struct WizardCommand;
+
+/// Starts the wizard for the admin, in the channel they used the command in.
+impl Command for WizardCommand {
+    fn options(&self) -> Arc<CommandOptions> {
+        let mut options = CommandOptions::default();
+        options.desc = Some(
+            "Sets up the polls step by step, asking questions in this channel.".to_string(),
+        );
+        options.help_available = true;
+        options.max_args = Some(0);
+
+        Arc::new(options)
+    }
+
+    fn execute(&self, _ctx: &mut Context, msg: &Message, _args: Args) -> Result<(), CommandError> {
+        // Unwrap is safe here because this command is only available in servers.
+        let server_id = msg.guild_id.unwrap();
+
+        let content = format!(
+            "{}\n{}",
+            tr(Some(server_id), "pm.wizard.start"),
+            tr(Some(server_id), "pm.wizard.channel")
+        );
+        msg.channel_id.send_message(|m| m.content(content))?;
+
+        conversation::start(
+            msg.channel_id,
+            msg.author.id,
+            Box::new(Wizard {
+                server_id,
+                step: Step::Channel,
+                days: String::new(),
+                start: (0, 0),
+                server: Server::default(),
+            }),
+        );
+
+        Ok(())
+    }
+}
+
+/// The question being answered.
+#[derive(Clone, Copy, PartialEq)]
+enum Step {
+    Channel,
+    Days,
+    Start,
+    End,
+    Roles,
+    Games,
+}
+
+struct Wizard {
+    server_id: GuildId,
+    step: Step,
+    days: String,
+    start: (u32, u32),
+    server: Server,
+}
+
+impl Wizard {
+    /// Handles an answer. Returns the next message to send, or an error message if the answer
+    /// isn't valid.
+    fn step(&mut self, msg: &Message) -> Result<String, String> {
+        let server_id = Some(self.server_id);
+        let text = msg.content.trim();
+
+        match self.step {
+            Step::Channel => {
+                let channel_id = text
+                    .parse::<ChannelId>()
+                    .ok()
+                    .filter(|c| match c.to_channel_cached() {
+                        Some(Channel::Guild(ref c)) => c.read().guild_id == self.server_id,
+                        _ => false,
+                    }).ok_or_else(|| tr(server_id, "pm.wizard.bad_channel"))?;
+                self.server.channel_id = channel_id;
+                self.step = Step::Days;
+                Ok(tr(server_id, "pm.wizard.days"))
+            }
+            Step::Days => {
+                self.days =
+                    schedule::parse_days(text).ok_or_else(|| tr(server_id, "pm.wizard.bad_days"))?;
+                self.step = Step::Start;
+                Ok(tr(server_id, "pm.wizard.start_time"))
+            }
+            Step::Start => {
+                self.start =
+                    schedule::parse_time(text).ok_or_else(|| tr(server_id, "pm.wizard.bad_time"))?;
+                self.step = Step::End;
+                Ok(tr(server_id, "pm.wizard.end_time"))
+            }
+            Step::End => {
+                let end =
+                    schedule::parse_time(text).ok_or_else(|| tr(server_id, "pm.wizard.bad_time"))?;
+                if end == self.start {
+                    return Err(tr(server_id, "pm.wizard.end_at_start"));
+                }
+                // An end earlier in the day is on the next day: given relative to the start, the
+                // days of the end are shifted.
+                let minutes = |(hour, minute): (u32, u32)| hour * 60 + minute;
+                let duration = (minutes(end) + 24 * 60 - minutes(self.start)) % (24 * 60);
+                let (start, end) = schedule::parse_events(
+                    self.server_id,
+                    &schedule::to_cron(&self.days, self.start),
+                    &format!("+{}m", duration),
+                )?;
+                schedule::check_events(self.server_id, &start, &end)?;
+                self.server.start = start;
+                self.server.end = end;
+                self.step = Step::Roles;
+                Ok(tr(server_id, "pm.wizard.roles"))
+            }
+            Step::Roles => {
+                self.server.role_ids = match text {
+                    "none" => None,
+                    "everyone" => Some(Vec::new()),
+                    _ if !msg.mention_roles.is_empty() => Some(msg.mention_roles.clone()),
+                    _ => return Err(tr(server_id, "pm.wizard.bad_roles")),
+                };
+                self.step = Step::Games;
+                Ok(tr(server_id, "pm.wizard.game"))
+            }
+            Step::Games => {
+                let game = self.parse_game(msg)?;
+                let name = format!("{} {}", game.emoji, game.name);
+                self.server.games.push(game);
+                Ok(tr_with(server_id, "pm.wizard.game_added", &[("game", name)]))
+            }
+        }
+    }
+
+    /// Reads a game: `<name> <emoji> <#channel> [@role...]`.
+    fn parse_game(&self, msg: &Message) -> Result<GameInfo, String> {
+        let server_id = Some(self.server_id);
+        let bad_game = || tr(server_id, "pm.wizard.bad_game");
+
+        let mut args = Args::new(&msg.content, &[" ".to_string()]);
+        let name = args.single_quoted::<String>().map_err(|_| bad_game())?;
+        let emoji = args.single::<String>().map_err(|_| bad_game())?;
+        let emoji = match parse_emoji(&emoji) {
+            Some(e) => ReactionType::Custom {
+                animated: false,
+                id: e.id,
+                name: Some(e.name),
+            },
+            None => ReactionType::Unicode(emoji),
+        };
+        let channel_id = args.single::<ChannelId>().map_err(|_| bad_game())?;
+
+        if self.server.games.iter().any(|g| g.name == name) {
+            return Err(tr_with(
+                server_id,
+                "pm.import.duplicate_game",
+                &[("game", name)],
+            ));
+        }
+        if self.server.games.iter().any(|g| g.emoji == emoji) {
+            return Err(tr_with(
+                server_id,
+                "pm.import.duplicate_emoji",
+                &[("emoji", emoji.to_string())],
+            ));
+        }
+
+        Ok(GameInfo {
+            name,
+            emoji,
+            role_ids: Some(msg.mention_roles.clone()).filter(|r| !r.is_empty()),
+            channel_id,
+        })
+    }
+
+    /// Puts the configuration in the admin's draft and shows it.
+    fn finish(&self, msg: &Message) -> Result<(), String> {
+        let server_id = Some(self.server_id);
+        send_paginated(
+            msg,
+            display_server(
+                self.server_id,
+                &self.server,
+                tr(server_id, "pm.wizard.done"),
+            ),
+        ).map_err(|e| e.to_string())?;
+
+        let key = (self.server_id, msg.author.id);
+        write_drafts().insert(key, Draft::new(self.server.clone()));
+        if let Some(warning) = other_editors(key) {
+            msg.channel_id
+                .send_message(|m| m.content(warning))
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Conversation for Wizard {
+    fn answer(&mut self, msg: &Message) -> bool {
+        let server_id = Some(self.server_id);
+        let text = msg.content.trim();
+
+        let (reply, goes_on) = if text == "cancel" {
+            (Some(tr(server_id, "pm.wizard.cancelled")), false)
+        } else if text == "done" && self.step == Step::Games {
+            if self.server.games.is_empty() {
+                (Some(tr(server_id, "pm.wizard.no_games")), true)
+            } else {
+                match self.finish(msg) {
+                    Ok(()) => (None, false),
+                    Err(e) => (Some(tr_with(server_id, "error", &[("error", e)])), false),
+                }
+            }
+        } else {
+            match self.step(msg) {
+                Ok(question) => (Some(question), true),
+                Err(error) => (Some(error), true),
+            }
+        };
+
+        if let Some(reply) = reply {
+            if let Err(e) = msg.channel_id.send_message(|m| m.content(reply)) {
+                warn!("Couldn't send message to server {}: {:?}", self.server_id, e);
+            }
+        }
+
+        goes_on
+    }
+}
@@ -1,48 +1,361 @@
-pub struct FoldStrlenState {
-    strings: Vec<Vec<String>>,
-    partition_size: usize,
-    current_character_count: usize,
-    total_character_count: usize,
-}
-
-impl FoldStrlenState {
-    pub fn new(nb_chars: usize) -> Self {
-        FoldStrlenState {
-            strings: Vec::new(),
-            partition_size: nb_chars,
-            current_character_count: 0,
-            total_character_count: 0,
+use serenity::builder::CreateEmbed;
+use serenity::model::prelude::*;
+use serenity::Result as SerenityResult;
+
+use serde_json::Value;
+
+// Discord's limits on embeds. They're all counted in characters, not bytes.
+pub const EMBED_TITLE_LIMIT: usize = 256;
+pub const EMBED_DESCRIPTION_LIMIT: usize = 2048;
+pub const EMBED_FIELD_NAME_LIMIT: usize = 256;
+pub const EMBED_FIELD_VALUE_LIMIT: usize = 1024;
+pub const EMBED_FOOTER_LIMIT: usize = 2048;
+pub const EMBED_AUTHOR_LIMIT: usize = 256;
+pub const EMBED_FIELD_COUNT_LIMIT: usize = 25;
+pub const EMBED_TOTAL_LIMIT: usize = 6000;
+
+/// Characters kept free in every embed for the page number `send_paginated` adds to the footer.
+pub const PAGE_NUMBER_ROOM: usize = 32;
+
+/// A logical part of an embed: a name and a list of lines. The lines are put in as few fields as
+/// possible; when they don't fit in one, the next fields are named after the section followed by
+/// the "continued" marker.
+pub struct Section {
+    name: String,
+    lines: Vec<String>,
+    separator: &'static str,
+    inline: bool,
+}
+
+impl Section {
+    pub fn new<N: Into<String>>(name: N, lines: Vec<String>) -> Self {
+        Section {
+            name: name.into(),
+            lines,
+            separator: "\n",
+            inline: false,
         }
     }
 
-    pub fn extract(self) -> Vec<Vec<String>> {
-        self.strings
+    /// Sets the string put between lines (a newline by default).
+    pub fn separator(mut self, separator: &'static str) -> Self {
+        self.separator = separator;
+        self
     }
-}
 
-pub type FoldStrlenStateResult = Result<FoldStrlenState, String>;
+    /// Sets whether the fields of the section are inline (they aren't by default).
+    pub fn inline(mut self, inline: bool) -> Self {
+        self.inline = inline;
+        self
+    }
 
-pub fn fold_by_strlen(mut state: FoldStrlenState, item: String) -> FoldStrlenStateResult {
-    if item.len() > state.partition_size {
-        return Err("A string is too long.".to_string());
+    /// Splits the section into fields, as (name, value, inline) tuples.
+    fn into_fields(self, cont: &str) -> Vec<(String, String, bool)> {
+        let separator_len = self.separator.chars().count();
+        let mut values: Vec<(String, usize)> = Vec::new();
+        for line in self.lines {
+            let line = truncate(line, EMBED_FIELD_VALUE_LIMIT);
+            let line_len = line.chars().count();
+            match values.last_mut() {
+                Some((ref mut value, ref mut len))
+                    if *len + separator_len + line_len <= EMBED_FIELD_VALUE_LIMIT =>
+                {
+                    value.push_str(self.separator);
+                    value.push_str(&line);
+                    *len += separator_len + line_len;
+                    continue;
+                }
+                _ => (),
+            }
+            values.push((line, line_len));
+        }
+
+        let name = truncate(self.name, EMBED_FIELD_NAME_LIMIT);
+        let name_cont = truncate(format!("{} {}", name, cont), EMBED_FIELD_NAME_LIMIT);
+        let inline = self.inline;
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(i, (value, _))| {
+                let name = if i == 0 { name.clone() } else { name_cont.clone() };
+                (name, value, inline)
+            }).collect()
     }
+}
 
-    let mut vector = if state.current_character_count + item.len() > state.partition_size || state.strings.is_empty() {
-        // Need to allocate a new vector to store the next lines.
-        state.current_character_count = 0;
-        Vec::new()
-    } else {
-        // Get the last vector, or an empty one if no work has been done prior.
-        // Unwrapping here should be safe because all the paths give a Vec.
-        // We also ensured that the state is not empty.
-        state.strings.pop().unwrap()
+/// Cuts a string to `limit` characters, marking the cut with an ellipsis.
+pub fn truncate(text: String, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text;
+    }
+    let mut text = text.chars().take(limit - 1).collect::<String>();
+    text.push('…');
+    text
+}
+
+/// Number of characters Discord counts for an embed: title, description, footer, author name and
+/// fields.
+pub fn embed_length(embed: &CreateEmbed) -> usize {
+    let text_length = |value: Option<&Value>| {
+        value
+            .and_then(Value::as_str)
+            .map_or(0, |s| s.chars().count())
     };
 
-    state.current_character_count += item.len();
-    state.total_character_count += item.len();
-    vector.push(item);
+    let fields = match embed.0.get(&"fields") {
+        Some(Value::Array(fields)) => fields
+            .iter()
+            .map(|f| text_length(f.get("name")) + text_length(f.get("value")))
+            .sum(),
+        _ => 0,
+    };
+
+    text_length(embed.0.get(&"title"))
+        + text_length(embed.0.get(&"description"))
+        + text_length(embed.0.get(&"footer").and_then(|f| f.get("text")))
+        + text_length(embed.0.get(&"author").and_then(|a| a.get("name")))
+        + fields
+}
+
+/// Spreads sections over as many embeds as needed to respect Discord's limits. Every embed is a
+/// copy of `base` (with its title, description, colour...) with fields added; `cont` is appended
+/// to the names of the fields continuing a section. Empty sections are left out.
+/// The title, description, footer and author of `base` are truncated to their limits. Room is kept
+/// for a page number, so the embeds can be given to `send_paginated`.
+pub fn paginate_embed(base: &CreateEmbed, sections: Vec<Section>, cont: &str) -> Vec<CreateEmbed> {
+    let mut base = base.clone();
+    let texts = [
+        ("title", None, EMBED_TITLE_LIMIT),
+        ("description", None, EMBED_DESCRIPTION_LIMIT),
+        ("footer", Some("text"), EMBED_FOOTER_LIMIT - PAGE_NUMBER_ROOM),
+        ("author", Some("name"), EMBED_AUTHOR_LIMIT),
+    ];
+    for &(key, subkey, limit) in &texts {
+        let value = match (base.0.get(&key).cloned(), subkey) {
+            (Some(Value::String(text)), None) => Value::String(truncate(text, limit)),
+            (Some(Value::Object(mut map)), Some(subkey)) => {
+                let text = map.get(subkey).and_then(Value::as_str).map(str::to_string);
+                if let Some(text) = text {
+                    map.insert(subkey.to_string(), Value::String(truncate(text, limit)));
+                }
+                Value::Object(map)
+            }
+            _ => continue,
+        };
+        // The map of an embed keeps every value inserted for a key.
+        base.0.remove(&key);
+        base.0.insert(key, value);
+    }
+    let base_length = embed_length(&base);
+    let total_limit = EMBED_TOTAL_LIMIT - PAGE_NUMBER_ROOM;
+
+    let mut embeds = Vec::new();
+    let mut current = base.clone();
+    let mut length = base_length;
+    let mut count = 0;
+    for (name, value, inline) in sections.into_iter().flat_map(|s| s.into_fields(cont)) {
+        let field_length = name.chars().count() + value.chars().count();
+        let full = count == EMBED_FIELD_COUNT_LIMIT || length + field_length > total_limit;
+        if count > 0 && full {
+            embeds.push(current);
+            current = base.clone();
+            length = base_length;
+            count = 0;
+        }
+        current = current.field(name, value, inline);
+        length += field_length;
+        count += 1;
+    }
+    embeds.push(current);
+
+    embeds
+}
+
+/// Sends embeds in a channel, one message each.
+pub fn send_embeds(
+    channel_id: ChannelId,
+    embeds: Vec<CreateEmbed>,
+) -> SerenityResult<Vec<Message>> {
+    embeds
+        .into_iter()
+        .map(|embed| channel_id.send_message(|m| m.embed(|_| embed)))
+        .collect()
+}
+
+/// Names of permissions, like `Manage guild, Send messages`.
+pub fn permission_names(permissions: Permissions) -> String {
+    if permissions.is_empty() {
+        return String::new();
+    }
+    format!("{:?}", permissions)
+        .split(" | ")
+        .map(|name| {
+            let name = name.to_lowercase().replace('_', " ");
+            let mut chars = name.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }).collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Replaces the `{name}` placeholders in a text by their values.
+pub fn fill_placeholders(text: &str, placeholders: &[(&str, String)]) -> String {
+    placeholders
+        .iter()
+        .fold(text.to_string(), |text, &(name, ref value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repeat(c: char, count: usize) -> String {
+        (0..count).map(|_| c).collect()
+    }
+
+    fn fields_of(embed: &CreateEmbed) -> Vec<(String, String)> {
+        match embed.0.get(&"fields") {
+            Some(Value::Array(fields)) => fields
+                .iter()
+                .map(|f| {
+                    (
+                        f["name"].as_str().unwrap().to_string(),
+                        f["value"].as_str().unwrap().to_string(),
+                    )
+                }).collect(),
+            _ => Vec::new(),
+        }
+    }
 
-    state.strings.push(vector);
+    #[test]
+    fn truncate_counts_characters() {
+        assert_eq!(truncate("short".to_string(), 5), "short");
+        assert_eq!(truncate("longer".to_string(), 5), "long…");
+        assert_eq!(truncate(String::new(), 1), "");
+
+        let text = repeat('é', EMBED_DESCRIPTION_LIMIT);
+        assert_eq!(truncate(text.clone(), EMBED_DESCRIPTION_LIMIT), text);
+        let cut = truncate(repeat('é', EMBED_DESCRIPTION_LIMIT + 1), EMBED_DESCRIPTION_LIMIT);
+        assert_eq!(cut.chars().count(), EMBED_DESCRIPTION_LIMIT);
+        assert!(cut.ends_with('…'));
+    }
+
+    #[test]
+    fn embed_length_counts_characters() {
+        let embed = CreateEmbed::default()
+            .title("ゲーム")
+            .description(repeat('é', 10))
+            .footer(|f| f.text("pied"))
+            .field("nom", "valeur", false);
+        assert_eq!(embed_length(&embed), 3 + 10 + 4 + 3 + 6);
+        assert_eq!(embed_length(&CreateEmbed::default()), 0);
+    }
+
+    #[test]
+    fn base_texts_are_truncated() {
+        let exact = CreateEmbed::default().description(repeat('é', EMBED_DESCRIPTION_LIMIT));
+        let embeds = paginate_embed(&exact, Vec::new(), "(cont)");
+        assert_eq!(embeds.len(), 1);
+        assert_eq!(embed_length(&embeds[0]), EMBED_DESCRIPTION_LIMIT);
+
+        let long = CreateEmbed::default()
+            .title(repeat('a', EMBED_TITLE_LIMIT + 1))
+            .description(repeat('é', EMBED_DESCRIPTION_LIMIT + 1));
+        let embeds = paginate_embed(&long, Vec::new(), "(cont)");
+        assert_eq!(
+            embed_length(&embeds[0]),
+            EMBED_TITLE_LIMIT + EMBED_DESCRIPTION_LIMIT
+        );
+    }
 
-    Ok(state)
+    #[test]
+    fn sections_are_split_into_fields() {
+        let lines = vec![repeat('x', 600), repeat('y', 600), repeat('z', 300)];
+        let embeds = paginate_embed(
+            &CreateEmbed::default(),
+            vec![Section::new("Jeux", lines), Section::new("Vide", Vec::new())],
+            "(suite)",
+        );
+        assert_eq!(embeds.len(), 1);
+        let fields = fields_of(&embeds[0]);
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].0, "Jeux");
+        assert_eq!(fields[0].1, repeat('x', 600));
+        assert_eq!(fields[1].0, "Jeux (suite)");
+        assert_eq!(fields[1].1, format!("{}\n{}", repeat('y', 600), repeat('z', 300)));
+
+        // A line too long for a field is cut.
+        let embeds = paginate_embed(
+            &CreateEmbed::default(),
+            vec![Section::new("Long", vec![repeat('é', EMBED_FIELD_VALUE_LIMIT + 1)])],
+            "(cont)",
+        );
+        let fields = fields_of(&embeds[0]);
+        assert_eq!(fields[0].1.chars().count(), EMBED_FIELD_VALUE_LIMIT);
+    }
+
+    #[test]
+    fn embeds_respect_the_total_limit() {
+        // Six fields fill an embed but for the room kept for the page number.
+        let sections = |count: usize, length: usize| {
+            (0..count)
+                .map(|_| Section::new("n", vec![repeat('é', length - 1)]))
+                .collect::<Vec<Section>>()
+        };
+        let full = (EMBED_TOTAL_LIMIT - PAGE_NUMBER_ROOM) / 6;
+        let embeds = paginate_embed(&CreateEmbed::default(), sections(6, full), "(cont)");
+        assert_eq!(embeds.len(), 1);
+        assert_eq!(embed_length(&embeds[0]), 6 * full);
+        let embeds = paginate_embed(&CreateEmbed::default(), sections(6, 1000), "(cont)");
+        assert_eq!(embeds.len(), 2);
+
+        let sections = |count: usize| sections(count, full);
+
+        let embeds = paginate_embed(&CreateEmbed::default(), sections(7), "(cont)");
+        assert_eq!(embeds.len(), 2);
+        assert_eq!(fields_of(&embeds[0]).len(), 6);
+        assert_eq!(fields_of(&embeds[1]).len(), 1);
+
+        // The base counts in every embed.
+        let base = CreateEmbed::default().title("titre");
+        let embeds = paginate_embed(&base, sections(6), "(cont)");
+        assert_eq!(embeds.len(), 2);
+        for embed in &embeds {
+            assert!(embed_length(embed) <= EMBED_TOTAL_LIMIT - PAGE_NUMBER_ROOM);
+        }
+    }
+
+    #[test]
+    fn embeds_respect_the_field_count_limit() {
+        let sections = (0..EMBED_FIELD_COUNT_LIMIT + 1)
+            .map(|i| Section::new(i.to_string(), vec!["line".to_string()]))
+            .collect();
+        let embeds = paginate_embed(&CreateEmbed::default(), sections, "(cont)");
+        assert_eq!(embeds.len(), 2);
+        assert_eq!(fields_of(&embeds[0]).len(), EMBED_FIELD_COUNT_LIMIT);
+        assert_eq!(fields_of(&embeds[1]).len(), 1);
+    }
+
+    #[test]
+    fn permissions_are_named() {
+        assert_eq!(permission_names(Permissions::MANAGE_GUILD), "Manage guild");
+        assert_eq!(
+            permission_names(Permissions::SEND_MESSAGES | Permissions::ADD_REACTIONS),
+            "Add reactions, Send messages"
+        );
+        assert_eq!(permission_names(Permissions::empty()), "");
+    }
+
+    #[test]
+    fn placeholders_are_filled() {
+        let filled = fill_placeholders(
+            "{game}: {count} joueurs, {unknown}",
+            &[("game", "Échecs".to_string()), ("count", "3".to_string())],
+        );
+        assert_eq!(filled, "Échecs: 3 joueurs, {unknown}");
+    }
 }